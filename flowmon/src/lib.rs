@@ -21,11 +21,18 @@
 
 */
 
-use std::{collections::HashMap, hash::Hash};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::mpsc::{self, Receiver, Sender, SyncSender},
+    thread,
+};
 
+pub mod ingress;
 mod tcp;
 mod udp;
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum PacketDirection {
     ClientToServer,
     ServerToClient,
@@ -49,6 +56,10 @@ impl TcpFlags {
     pub fn is_fin(&self) -> bool {
         self.0 & 0x01 != 0
     }
+
+    pub fn is_rst(&self) -> bool {
+        self.0 & 0x04 != 0
+    }
 }
 
 pub struct IPv4Flags(u8);
@@ -67,6 +78,14 @@ impl IPv4Flags {
     }
 }
 
+/// An IP address of either version, wide enough to carry a v6 address
+/// without forcing v4 traffic to pay for it.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IpAddr {
+    V4(u32),
+    V6(u128),
+}
+
 pub enum NetworkMeta {
     Pending,
     IPv4Fragment {
@@ -81,6 +100,10 @@ pub enum NetworkMeta {
         destination_ip: u32,
         flags: IPv4Flags,
     },
+    IPv6 {
+        source_ip: u128,
+        destination_ip: u128,
+    },
     Unknown,
 }
 
@@ -122,8 +145,8 @@ impl<'a> PacketMeta<'a> {
 }
 
 pub struct SessionTuple {
-    pub client_ip: u32,
-    pub server_ip: u32,
+    pub client_ip: IpAddr,
+    pub server_ip: IpAddr,
     pub client_port: u16,
     pub server_port: u16,
     pub protocol: u8,
@@ -131,8 +154,8 @@ pub struct SessionTuple {
 
 impl SessionTuple {
     pub fn new(
-        client_ip: u32,
-        server_ip: u32,
+        client_ip: IpAddr,
+        server_ip: IpAddr,
         client_port: u16,
         server_port: u16,
         protocol: u8,
@@ -149,10 +172,10 @@ impl SessionTuple {
 
 /// A key for a session that is used to identify the session in a hash table.
 /// The IP addresses are used to sort the IPs and ports, so that the key can be used bi-directionally.
-#[derive(PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SessionKey {
-    pub lesser_ip: u32,
-    pub greater_ip: u32,
+    pub lesser_ip: IpAddr,
+    pub greater_ip: IpAddr,
     pub lesser_port: u16,
     pub greater_port: u16,
     pub protocol: u8,
@@ -160,8 +183,8 @@ pub struct SessionKey {
 
 impl SessionKey {
     pub fn new(
-        source_ip: u32,
-        destination_ip: u32,
+        source_ip: IpAddr,
+        destination_ip: IpAddr,
         source_port: u16,
         destination_port: u16,
         protocol: u8,
@@ -180,6 +203,25 @@ impl SessionKey {
             protocol,
         }
     }
+
+    /// If this is an RTCP flow under the RFC 3550 convention of binding
+    /// RTCP to the port directly above its RTP flow's, returns that RTP
+    /// flow's key. Both endpoints' ports are checked (rather than just
+    /// one) because `lesser_port`/`greater_port` are sorted by IP, not by
+    /// client/server role, so either one could be the RTCP-bound port.
+    fn paired_rtp_key(&self) -> Option<SessionKey> {
+        if self.protocol != 17 || self.lesser_port % 2 == 0 || self.greater_port % 2 == 0 {
+            return None;
+        }
+
+        Some(SessionKey {
+            lesser_ip: self.lesser_ip,
+            greater_ip: self.greater_ip,
+            lesser_port: self.lesser_port - 1,
+            greater_port: self.greater_port - 1,
+            protocol: self.protocol,
+        })
+    }
 }
 
 pub struct SessionStats {
@@ -203,6 +245,22 @@ pub enum SessionInfo {
         status: u16,
         latency: u64,
     },
+    Rtp {
+        payload_type: u8,
+        packets_lost: i64,
+        jitter: f64,
+        ssrc: u32,
+    },
+    /// Connection-level TCP performance: handshake RTT and server response
+    /// time, the latter kept as min/max/count/sum so an average can be
+    /// recovered as `response_time_sum / response_time_count`.
+    Tcp {
+        connection_rtt: Option<u32>,
+        response_time_count: u64,
+        response_time_sum: u64,
+        response_time_min: Option<u32>,
+        response_time_max: Option<u32>,
+    },
 }
 
 pub enum TransportSession {
@@ -222,7 +280,7 @@ pub struct Session {
 }
 
 impl Session {
-    pub fn new(tuple: SessionTuple, stats: SessionStats) -> Self {
+    pub fn new(tuple: SessionTuple, stats: SessionStats, now: u64) -> Self {
         let transport = match tuple.protocol {
             6 => TransportSession::Tcp(tcp::TcpSession::new()),
             17 => TransportSession::Udp(udp::UdpSession::new()),
@@ -231,9 +289,9 @@ impl Session {
 
         Self {
             tuple,
-            start_time: 0,
-            last_tx_time: 0,
-            last_rx_time: 0,
+            start_time: now,
+            last_tx_time: now,
+            last_rx_time: now,
             stats,
             info: SessionInfo::Pending,
             transport,
@@ -250,33 +308,81 @@ impl Session {
         )
     }
 
-    pub fn handle(&mut self, direction: PacketDirection, payload: &[u8]) {
+    /// Returns an RTCP Sender/Receiver Report if `payload` was one, so the
+    /// caller (`FlowMonShard`, which alone can see the paired RTP flow on
+    /// the adjacent port) can route it there.
+    pub fn handle(
+        &mut self,
+        direction: PacketDirection,
+        payload: &[u8],
+        now: u64,
+    ) -> Option<udp::RtcpReport> {
         match direction {
             PacketDirection::ServerToClient => {
                 self.stats.packets_rx += 1;
                 self.stats.bytes_rx += payload.len() as u64;
-                self.last_rx_time = 0; // TODO: set to current time
+                self.last_rx_time = now;
             }
             PacketDirection::ClientToServer => {
                 self.stats.packets_tx += 1;
                 self.stats.bytes_tx += payload.len() as u64;
-                self.last_tx_time = 0; // TODO: set to current time
+                self.last_tx_time = now;
             }
         }
 
         match &mut self.transport {
             TransportSession::Tcp(session) => {
-                session.process_packet(payload, direction);
+                session.process_packet(payload, direction, now);
+                self.info = session.info().unwrap_or(SessionInfo::Pending);
+                None
             },
             TransportSession::Udp(session) => {
-                session.process_packet(payload, direction);
+                // `payload` is the whole UDP datagram; demultiplexing works
+                // on what follows its 8-byte header.
+                let udp_payload = payload.get(8..).unwrap_or(&[]);
+                let rtcp_report = session.process_packet(udp_payload, direction, now);
+                self.info = session.info().unwrap_or(SessionInfo::Pending);
+                rtcp_report
+            }
+            _ => None,
+        }
+    }
+
+    /// Applies an RTCP report pulled from this session's paired RTCP flow
+    /// (see `SessionKey::paired_rtp_key`) to whichever RTP stream it
+    /// describes.
+    fn apply_remote_rtcp_report(&mut self, report: &udp::RtcpReport) {
+        if let TransportSession::Udp(session) = &mut self.transport {
+            session.apply_remote_rtcp_report(report);
+            self.info = session.info().unwrap_or(SessionInfo::Pending);
+        }
+    }
+
+    /// Whether this session should be reaped: a TCP session whose RFC 793
+    /// teardown FSM (`tcp::TcpSession`) has reached its terminal `Closed`
+    /// state - which only happens once both sides' FINs have been seen, the
+    /// first of them acked, and the `TimeWait` window has elapsed - or any
+    /// session idle past its
+    /// protocol's timeout.
+    fn is_expired(&self, now: u64, tcp_idle_timeout: u64, udp_idle_timeout: u64) -> bool {
+        if let TransportSession::Tcp(session) = &self.transport {
+            if session.is_closed(now) {
+                return true;
             }
-            _ => {}
         }
+
+        let idle_timeout = match self.tuple.protocol {
+            6 => tcp_idle_timeout,
+            17 => udp_idle_timeout,
+            _ => return false,
+        };
+
+        let last_activity = self.last_tx_time.max(self.last_rx_time);
+        now.saturating_sub(last_activity) >= idle_timeout
     }
 
     #[inline]
-    pub fn pkt_direction(&self, source_ip: u32) -> PacketDirection {
+    pub fn pkt_direction(&self, source_ip: IpAddr) -> PacketDirection {
         if source_ip == self.tuple.client_ip {
             PacketDirection::ClientToServer
         } else {
@@ -285,39 +391,258 @@ impl Session {
     }
 }
 
-pub struct FlowMon {
+/// Default time a partially-reassembled IPv4 datagram is kept before its
+/// fragments are discarded, bounding memory against fragmentation-based
+/// DoS. Same units as the `now` timestamps threaded through
+/// `FlowMonShard::handle_ipvx_packet`.
+const DEFAULT_FRAGMENT_TIMEOUT: u64 = 30_000;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+struct FragmentKey {
+    source_ip: u32,
+    destination_ip: u32,
+    identifier: u16,
+    protocol: u8,
+}
+
+/// One outstanding gap in a `FragmentBuffer`'s reassembled datagram, as an
+/// inclusive `[first, last]` byte range per RFC 815.
+struct FragmentHole {
+    first: usize,
+    last: usize,
+}
+
+/// In-progress reassembly of one IPv4 datagram, following RFC 815's
+/// hole-list algorithm: a single hole `(0, usize::MAX)` is whittled down
+/// as fragments arrive, and the datagram is complete once no holes remain.
+struct FragmentBuffer {
+    data: Vec<u8>,
+    holes: Vec<FragmentHole>,
+    last_seen: u64,
+}
+
+impl FragmentBuffer {
+    fn new(now: u64) -> Self {
+        Self {
+            data: Vec::new(),
+            holes: vec![FragmentHole {
+                first: 0,
+                last: usize::MAX,
+            }],
+            last_seen: now,
+        }
+    }
+
+    /// Folds one arriving fragment covering `[frag_first, frag_last]` into
+    /// the hole list, per RFC 815. Returns `true` once the datagram is
+    /// fully reassembled, i.e. no holes remain.
+    ///
+    /// Only bytes that actually fall within a remaining hole are copied
+    /// into `data` - a fragment that doesn't overlap any hole is a pure
+    /// duplicate and is ignored outright, and a fragment that partially
+    /// overlaps a hole has only its hole-covered portion copied, so a
+    /// duplicate/overlapping retransmission can't clobber bytes another
+    /// fragment already reassembled.
+    fn insert(
+        &mut self,
+        frag_first: usize,
+        frag_last: usize,
+        more_fragments: bool,
+        payload: &[u8],
+    ) -> bool {
+        if self.data.len() <= frag_last {
+            self.data.resize(frag_last + 1, 0);
+        }
+
+        let mut i = 0;
+        while i < self.holes.len() {
+            let hole_first = self.holes[i].first;
+            let hole_last = self.holes[i].last;
+
+            if frag_first > hole_last || frag_last < hole_first {
+                i += 1;
+                continue;
+            }
+
+            let copy_first = frag_first.max(hole_first);
+            let copy_last = frag_last.min(hole_last);
+            let payload_offset = copy_first - frag_first;
+            let copy_len = copy_last - copy_first + 1;
+            self.data[copy_first..=copy_last]
+                .copy_from_slice(&payload[payload_offset..payload_offset + copy_len]);
+
+            self.holes.remove(i);
+            let mut inserted = 0;
+
+            if frag_first > hole_first {
+                self.holes.insert(
+                    i,
+                    FragmentHole {
+                        first: hole_first,
+                        last: frag_first - 1,
+                    },
+                );
+                inserted += 1;
+            }
+
+            if frag_last < hole_last && more_fragments {
+                self.holes.insert(
+                    i + inserted,
+                    FragmentHole {
+                        first: frag_last + 1,
+                        last: hole_last,
+                    },
+                );
+                inserted += 1;
+            }
+
+            i += inserted;
+        }
+
+        self.holes.is_empty()
+    }
+}
+
+/// Default time an idle TCP session is kept before `FlowMonShard::reap`
+/// expires it. Same units as the `now` timestamps threaded through
+/// `FlowMonShard::handle_ipvx_packet`.
+const DEFAULT_TCP_IDLE_TIMEOUT: u64 = 60_000;
+/// Default time an idle UDP session is kept before `FlowMonShard::reap`
+/// expires it. UDP has no teardown signal, so it relies on this timeout
+/// alone and it is kept much shorter than `DEFAULT_TCP_IDLE_TIMEOUT`.
+const DEFAULT_UDP_IDLE_TIMEOUT: u64 = 10_000;
+
+/// One shard of the flow table: its own session map, fragment-reassembly
+/// state and a dedicated worker thread. A `SessionKey` always hashes to
+/// the same shard (see `shard_index_for`), so a shard never needs to
+/// coordinate with the others and can mutate its maps without locking.
+struct FlowMonShard {
     sessions: HashMap<SessionKey, Session>,
     // TODO: object pool to cache stat objects for each type
+    fragments: HashMap<FragmentKey, FragmentBuffer>,
+    fragment_timeout: u64,
+    tcp_idle_timeout: u64,
+    udp_idle_timeout: u64,
+    // RTCP reports waiting on an RTP flow that hasn't been seen yet, keyed
+    // by that RTP flow's `SessionKey` and paired with the time they
+    // arrived. Only correlates within one shard - an RTP/RTCP pair hashed
+    // onto different shards by `shard_index_for` never meets up. Pruned by
+    // age in `reap`, much as `fragments` is pruned by age in
+    // `handle_ipv4_fragmentation`, since the RTP flow it's waiting on may
+    // never show up at all.
+    rtcp_reports: HashMap<SessionKey, (udp::RtcpReport, u64)>,
 }
 
-impl FlowMon {
-    pub fn new() -> Self {
+impl FlowMonShard {
+    fn new() -> Self {
+        Self::with_timeouts(
+            DEFAULT_FRAGMENT_TIMEOUT,
+            DEFAULT_TCP_IDLE_TIMEOUT,
+            DEFAULT_UDP_IDLE_TIMEOUT,
+        )
+    }
+
+    fn with_timeouts(fragment_timeout: u64, tcp_idle_timeout: u64, udp_idle_timeout: u64) -> Self {
         Self {
             sessions: HashMap::new(),
+            fragments: HashMap::new(),
+            fragment_timeout,
+            tcp_idle_timeout,
+            udp_idle_timeout,
+            rtcp_reports: HashMap::new(),
         }
     }
 
+    /// Expires closed or idle sessions, returning each one's final
+    /// `SessionStats`/`SessionInfo` record (on `Session`) before it is
+    /// dropped from the table. Also prunes `rtcp_reports` entries that
+    /// have sat unclaimed past the UDP idle timeout, so an RTP flow that
+    /// never shows up doesn't leak its report forever.
+    fn reap(&mut self, now: u64) -> Vec<Session> {
+        let udp_idle_timeout = self.udp_idle_timeout;
+        self.rtcp_reports
+            .retain(|_, (_, received_at)| now.saturating_sub(*received_at) < udp_idle_timeout);
+
+        let expired_keys: Vec<SessionKey> = self
+            .sessions
+            .iter()
+            .filter(|(_, session)| session.is_expired(now, self.tcp_idle_timeout, self.udp_idle_timeout))
+            .map(|(key, _)| *key)
+            .collect();
+
+        expired_keys
+            .into_iter()
+            .filter_map(|key| self.sessions.remove(&key))
+            .collect()
+    }
+
     fn handle_ipv4_fragmentation(
         &mut self,
         ip_hdr: &[u8],
+        proto: u8,
         more_fragments: bool,
         scaled_fragment_offset: usize,
         transport_data: &[u8],
+        now: u64,
     ) {
         let total_length: u16 = u16::from_be_bytes(ip_hdr[2..4].try_into().unwrap());
         let identification: u16 = u16::from_be_bytes(ip_hdr[4..6].try_into().unwrap());
 
-        // Handle IPv4 fragmentation
-        // Check if the packet is fragmented and reassemble it if necessary
-        // Store the reassembled packet in a buffer for further processing
-        // If it's not fragmented, process the packet normally
+        let fragment_data_len = (total_length as usize).saturating_sub(ip_hdr.len());
+        if fragment_data_len == 0 || transport_data.len() < fragment_data_len {
+            return;
+        }
+
+        let source_ip = u32::from_be_bytes(ip_hdr[12..16].try_into().unwrap());
+        let destination_ip = u32::from_be_bytes(ip_hdr[16..20].try_into().unwrap());
+
+        let fragment_timeout = self.fragment_timeout;
+        self.fragments
+            .retain(|_, buffer| now.saturating_sub(buffer.last_seen) < fragment_timeout);
+
+        let key = FragmentKey {
+            source_ip,
+            destination_ip,
+            identifier: identification,
+            protocol: proto,
+        };
+
+        let buffer = self
+            .fragments
+            .entry(key)
+            .or_insert_with(|| FragmentBuffer::new(now));
+        buffer.last_seen = now;
+
+        let frag_first = scaled_fragment_offset;
+        let frag_last = frag_first + fragment_data_len - 1;
+
+        let complete = buffer.insert(
+            frag_first,
+            frag_last,
+            more_fragments,
+            &transport_data[..fragment_data_len],
+        );
+
+        if !complete {
+            return;
+        }
+
+        let buffer = self.fragments.remove(&key).unwrap();
+        self.handle_transport_packet(
+            proto,
+            IpAddr::V4(source_ip),
+            IpAddr::V4(destination_ip),
+            &buffer.data,
+            now,
+        );
     }
 
-    fn handle_ipv4_udp_packet(
+    fn handle_udp_packet(
         &mut self,
-        source_address: [u8; 4],
-        destination_address: [u8; 4],
+        source_ip: IpAddr,
+        destination_ip: IpAddr,
         transport_data: &[u8],
+        now: u64,
     ) {
         let udp_header_length: usize = 8;
 
@@ -328,9 +653,6 @@ impl FlowMon {
         let source_port = u16::from_be_bytes([transport_data[0], transport_data[1]]);
         let destination_port = u16::from_be_bytes([transport_data[2], transport_data[3]]);
 
-        let source_ip = u32::from_be_bytes(source_address);
-        let destination_ip = u32::from_be_bytes(destination_address);
-
         let length = u16::from_be_bytes([transport_data[4], transport_data[5]]) as usize;
 
         if transport_data.len() < length {
@@ -348,19 +670,34 @@ impl FlowMon {
                     bytes_rx: 0,
                     bytes_tx: 0,
                 },
+                now,
             )
         });
 
         let direction = session.pkt_direction(source_ip);
 
-        session.handle(direction, &transport_data[..length]);
+        let rtcp_report = session.handle(direction, &transport_data[..length], now);
+
+        match rtcp_report {
+            Some(report) => {
+                if let Some(rtp_key) = key.paired_rtp_key() {
+                    self.rtcp_reports.insert(rtp_key, (report, now));
+                }
+            }
+            None => {
+                if let Some((report, _)) = self.rtcp_reports.remove(&key) {
+                    session.apply_remote_rtcp_report(&report);
+                }
+            }
+        }
     }
 
-    fn handle_ipv4_tcp_packet(
+    fn handle_tcp_packet(
         &mut self,
-        source_address: [u8; 4],
-        destination_address: [u8; 4],
+        source_ip: IpAddr,
+        destination_ip: IpAddr,
         transport_data: &[u8],
+        now: u64,
     ) {
         let tcp_header_length: usize = 20;
         if transport_data.len() < tcp_header_length {
@@ -370,9 +707,6 @@ impl FlowMon {
         let source_port = u16::from_be_bytes([transport_data[0], transport_data[1]]);
         let destination_port = u16::from_be_bytes([transport_data[2], transport_data[3]]);
 
-        let source_ip = u32::from_be_bytes(source_address);
-        let destination_ip = u32::from_be_bytes(destination_address);
-
         let key = SessionKey::new(source_ip, destination_ip, source_port, destination_port, 6);
 
         let session = self.sessions.entry(key).or_insert_with(|| {
@@ -384,49 +718,118 @@ impl FlowMon {
                     bytes_rx: 0,
                     bytes_tx: 0,
                 },
+                now,
             )
         });
 
         let direction = session.pkt_direction(source_ip);
 
-        session.handle(direction, transport_data);
+        session.handle(direction, transport_data, now);
+    }
+
+    fn handle_transport_packet(
+        &mut self,
+        proto: u8,
+        source_ip: IpAddr,
+        destination_ip: IpAddr,
+        transport_data: &[u8],
+        now: u64,
+    ) {
+        match proto {
+            17 => {
+                self.handle_udp_packet(source_ip, destination_ip, transport_data, now);
+            }
+            6 => {
+                self.handle_tcp_packet(source_ip, destination_ip, transport_data, now);
+            }
+            _ => {
+                // Unsupported protocol
+            }
+        }
     }
 
-    fn handle_ipv4_packet(&mut self, ip_hdr: &[u8], transport_data: &[u8]) {
+    fn handle_ipv4_packet(&mut self, ip_hdr: &[u8], transport_data: &[u8], now: u64) {
         let flags: u8 = ip_hdr[6] >> 5;
         let more_fragments = flags & 0x1 != 0;
         let unscaled_fragment_offset: usize =
             (u16::from_be_bytes([ip_hdr[6], ip_hdr[7]]) & 0x1FFF) as usize;
 
+        let proto = ip_hdr[9];
+
         if more_fragments || unscaled_fragment_offset != 0 {
             self.handle_ipv4_fragmentation(
                 ip_hdr,
+                proto,
                 more_fragments,
                 8 * unscaled_fragment_offset,
                 transport_data,
+                now,
             );
             return;
         }
 
-        let proto = ip_hdr[9];
+        let source_ip = IpAddr::V4(u32::from_be_bytes(ip_hdr[12..16].try_into().unwrap()));
+        let destination_ip = IpAddr::V4(u32::from_be_bytes(ip_hdr[16..20].try_into().unwrap()));
 
-        let source_address: [u8; 4] = ip_hdr[12..16].try_into().unwrap();
-        let destination_address: [u8; 4] = ip_hdr[16..20].try_into().unwrap();
+        self.handle_transport_packet(proto, source_ip, destination_ip, transport_data, now);
+    }
 
-        match proto {
-            17 => {
-                self.handle_ipv4_udp_packet(source_address, destination_address, transport_data);
-            }
-            6 => {
-                self.handle_ipv4_tcp_packet(source_address, destination_address, transport_data);
+    /// Walks an IPv6 packet's extension-header chain (Hop-by-Hop, Routing,
+    /// Fragment, Destination Options) starting after the fixed 40-byte
+    /// header to find the real transport protocol and its payload.
+    ///
+    /// Fragmented IPv6 traffic is not reassembled here (unlike the IPv4
+    /// path in `handle_ipv4_fragmentation`), so a packet carrying a
+    /// Fragment header is handed to the transport parser as-is, which is
+    /// only correct for the first fragment.
+    fn handle_ipv6_packet(&mut self, ip_hdr: &[u8], packet: &[u8], now: u64) {
+        const IPV6_HEADER_LENGTH: usize = 40;
+
+        let mut next_header = ip_hdr[6];
+        let mut offset = IPV6_HEADER_LENGTH;
+
+        loop {
+            match next_header {
+                // Hop-by-Hop Options, Routing, Destination Options: a
+                // generic TLV header whose length is given in 8-octet
+                // units, excluding the first 8 octets.
+                0 | 43 | 60 => {
+                    if packet.len() < offset + 8 {
+                        return;
+                    }
+                    let header_ext_len = packet[offset + 1] as usize;
+                    next_header = packet[offset];
+                    offset += (header_ext_len + 1) * 8;
+                }
+                // Fragment header: fixed 8 octets.
+                44 => {
+                    if packet.len() < offset + 8 {
+                        return;
+                    }
+                    next_header = packet[offset];
+                    offset += 8;
+                }
+                _ => break,
             }
-            _ => {
-                // Unsupported protocol
+
+            if packet.len() < offset {
+                return;
             }
         }
+
+        let proto = next_header;
+
+        let source_ip = IpAddr::V6(u128::from_be_bytes(ip_hdr[8..24].try_into().unwrap()));
+        let destination_ip = IpAddr::V6(u128::from_be_bytes(ip_hdr[24..40].try_into().unwrap()));
+
+        self.handle_transport_packet(proto, source_ip, destination_ip, &packet[offset..], now);
     }
 
-    pub fn handle_ipvx_packet(&mut self, packet: &[u8]) {
+    /// `now` is the packet's arrival time, in the same units used
+    /// throughout this crate (e.g. `TcpSession::process_packet`'s `now`);
+    /// it drives the IPv4 reassembly buffer eviction in
+    /// `handle_ipv4_fragmentation`.
+    fn handle_ipvx_packet(&mut self, packet: &[u8], now: u64) {
         if packet.len() < 28 {
             // minimum length is 28 bytes - 20 bytes for IP header and 8 bytes for UDP header
             return;
@@ -436,20 +839,20 @@ impl FlowMon {
 
         match ip_version {
             4 => {
-                // IPv4
                 let ip_header_length: usize = ((packet[0] & 0x0F) * 4).into();
                 if packet.len() < ip_header_length {
                     return;
                 }
-                self.handle_ipv4_packet(&packet[0..ip_header_length], &packet[ip_header_length..]);
+                self.handle_ipv4_packet(&packet[0..ip_header_length], &packet[ip_header_length..], now);
             }
             6 => {
-                // No IPv6 support yet
-                return;
+                if packet.len() < 40 {
+                    return;
+                }
+                self.handle_ipv6_packet(&packet[0..40], packet, now);
             }
             _ => {
                 // Unknown IP version
-                return;
             }
         }
 
@@ -459,3 +862,231 @@ impl FlowMon {
         // Extract the relevant information and store it
     }
 }
+
+/// Depth of each shard's ingress queue. Bounded so a shard whose worker
+/// thread falls behind applies backpressure to `FlowMon::enqueue` (via a
+/// dropped packet) instead of letting memory grow without limit.
+const DEFAULT_SHARD_QUEUE_DEPTH: usize = 1024;
+
+/// One packet handed to a shard's worker thread: the raw bytes plus the
+/// arrival timestamp `FlowMonShard::handle_ipvx_packet` expects.
+struct IngressPacket {
+    data: Vec<u8>,
+    now: u64,
+}
+
+/// A shard's half of the channel pair, owned by `FlowMon`. The worker
+/// thread itself is detached rather than joined; it runs until its
+/// `sender` (and every clone of it) is dropped, at which point `recv`
+/// fails and the thread exits.
+struct ShardHandle {
+    sender: SyncSender<IngressPacket>,
+    _worker: thread::JoinHandle<()>,
+}
+
+/// Runs one shard's worker thread: pull packets off its queue, feed them
+/// through the normal parsing path, and forward whatever `reap` expires
+/// to `expired_tx` so `FlowMon::try_recv_expired` can surface it.
+fn run_shard(queue: Receiver<IngressPacket>, expired_tx: Sender<Session>) {
+    let mut shard = FlowMonShard::new();
+
+    while let Ok(packet) = queue.recv() {
+        shard.handle_ipvx_packet(&packet.data, packet.now);
+
+        for session in shard.reap(packet.now) {
+            if expired_tx.send(session).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// The fields of a packet that identify its conversation, used only to
+/// pick a shard - not the full `SessionKey`, since that only exists once
+/// a packet has been parsed far enough to build a `SessionTuple`. IPs and
+/// ports are sorted the same way `SessionKey::new` sorts them, so both
+/// directions of a conversation hash identically and land on the same
+/// shard.
+#[derive(Hash)]
+struct FlowFields {
+    lesser_ip: IpAddr,
+    greater_ip: IpAddr,
+    lesser_port: u16,
+    greater_port: u16,
+    protocol: u8,
+}
+
+impl FlowFields {
+    fn new(
+        source_ip: IpAddr,
+        destination_ip: IpAddr,
+        source_port: u16,
+        destination_port: u16,
+        protocol: u8,
+    ) -> Self {
+        let (lesser_ip, lesser_port, greater_ip, greater_port) = if source_ip < destination_ip {
+            (source_ip, source_port, destination_ip, destination_port)
+        } else {
+            (destination_ip, destination_port, source_ip, source_port)
+        };
+
+        Self {
+            lesser_ip,
+            greater_ip,
+            lesser_port,
+            greater_port,
+            protocol,
+        }
+    }
+}
+
+/// Best-effort extraction of `FlowFields`, just enough to shard packets
+/// consistently without redoing `FlowMonShard::handle_ipvx_packet`'s full
+/// parse (extension-header walking, fragment reassembly, etc). IPv4
+/// fragments after the first carry no transport header, so they're keyed
+/// on addresses and protocol alone - the same key the first fragment
+/// produces, since ports don't factor in - which keeps every fragment of
+/// one datagram on the same shard.
+fn peek_flow_fields(packet: &[u8]) -> Option<FlowFields> {
+    if packet.len() < 20 {
+        return None;
+    }
+
+    let ip_version = packet[0] >> 4;
+
+    match ip_version {
+        4 => {
+            let ip_header_length: usize = ((packet[0] & 0x0F) * 4).into();
+            if packet.len() < ip_header_length {
+                return None;
+            }
+
+            let protocol = packet[9];
+            let source_ip = IpAddr::V4(u32::from_be_bytes(packet[12..16].try_into().ok()?));
+            let destination_ip = IpAddr::V4(u32::from_be_bytes(packet[16..20].try_into().ok()?));
+            let unscaled_fragment_offset = u16::from_be_bytes([packet[6], packet[7]]) & 0x1FFF;
+
+            let transport = &packet[ip_header_length..];
+            let (source_port, destination_port) = if unscaled_fragment_offset == 0 && transport.len() >= 4 {
+                (
+                    u16::from_be_bytes([transport[0], transport[1]]),
+                    u16::from_be_bytes([transport[2], transport[3]]),
+                )
+            } else {
+                (0, 0)
+            };
+
+            Some(FlowFields::new(
+                source_ip,
+                destination_ip,
+                source_port,
+                destination_port,
+                protocol,
+            ))
+        }
+        6 => {
+            if packet.len() < 40 {
+                return None;
+            }
+
+            let protocol = packet[6];
+            let source_ip = IpAddr::V6(u128::from_be_bytes(packet[8..24].try_into().ok()?));
+            let destination_ip = IpAddr::V6(u128::from_be_bytes(packet[24..40].try_into().ok()?));
+
+            let transport = &packet[40..];
+            let (source_port, destination_port) = if transport.len() >= 4 {
+                (
+                    u16::from_be_bytes([transport[0], transport[1]]),
+                    u16::from_be_bytes([transport[2], transport[3]]),
+                )
+            } else {
+                (0, 0)
+            };
+
+            Some(FlowFields::new(
+                source_ip,
+                destination_ip,
+                source_port,
+                destination_port,
+                protocol,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Picks which shard owns a packet's conversation. A packet that can't be
+/// parsed even this far (too short, unknown IP version) still needs a
+/// deterministic shard, so it falls back to hashing the raw bytes.
+fn shard_index_for(packet: &[u8], shard_count: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+
+    match peek_flow_fields(packet) {
+        Some(fields) => fields.hash(&mut hasher),
+        None => packet.hash(&mut hasher),
+    }
+
+    (hasher.finish() % shard_count as u64) as usize
+}
+
+/// A multi-threaded flow table: `sessions` are partitioned across `N`
+/// shards, each with its own `HashMap` and worker thread, selected by
+/// `shard_index_for` so a given 5-tuple always lands on the same shard.
+/// That keeps every conversation's packets processed in order on one
+/// thread while unrelated flows run in parallel across the others, and
+/// lets each shard mutate its session map without locking.
+pub struct FlowMon {
+    shards: Vec<ShardHandle>,
+    expired_rx: Receiver<Session>,
+}
+
+impl FlowMon {
+    /// Shards across one worker per available CPU, falling back to a
+    /// single shard if that can't be determined.
+    pub fn new() -> Self {
+        let workers = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_workers(workers)
+    }
+
+    pub fn with_workers(workers: usize) -> Self {
+        let workers = workers.max(1);
+        let (expired_tx, expired_rx) = mpsc::channel();
+
+        let shards = (0..workers)
+            .map(|_| {
+                let (sender, queue) = mpsc::sync_channel(DEFAULT_SHARD_QUEUE_DEPTH);
+                let expired_tx = expired_tx.clone();
+                let worker = thread::spawn(move || run_shard(queue, expired_tx));
+                ShardHandle {
+                    sender,
+                    _worker: worker,
+                }
+            })
+            .collect();
+
+        Self { shards, expired_rx }
+    }
+
+    /// Ingress entry point: hands `packet` to whichever shard owns its
+    /// conversation. Returns `false` if that shard's queue is full or its
+    /// worker thread has died, in which case the packet is dropped rather
+    /// than blocking the caller.
+    pub fn enqueue(&self, packet: Vec<u8>, now: u64) -> bool {
+        if self.shards.is_empty() {
+            return false;
+        }
+
+        let index = shard_index_for(&packet, self.shards.len());
+        self.shards[index]
+            .sender
+            .try_send(IngressPacket { data: packet, now })
+            .is_ok()
+    }
+
+    /// Drains one expired session reported by any shard's `reap`, if one
+    /// is waiting. Non-blocking, so it's meant to be polled alongside
+    /// `enqueue` rather than awaited.
+    pub fn try_recv_expired(&self) -> Option<Session> {
+        self.expired_rx.try_recv().ok()
+    }
+}
@@ -1,17 +1,97 @@
-use crate::PacketDirection;
+use crate::{PacketDirection, SessionInfo};
+
+/// A TCP sequence or acknowledgment number, which wraps at 2^32.
+///
+/// Ordering is defined by the sign of the signed difference between two
+/// numbers (RFC 793 §3.3), so comparisons stay correct across wraparound
+/// instead of the naive `u32` ordering breaking near the boundary.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct SeqNumber(u32);
+
+impl SeqNumber {
+    fn new(value: u32) -> Self {
+        Self(value)
+    }
+
+    #[inline]
+    fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+impl PartialOrd for SeqNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SeqNumber {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.0.wrapping_sub(other.0) as i32).cmp(&0)
+    }
+}
+
+impl std::ops::Add<usize> for SeqNumber {
+    type Output = SeqNumber;
+
+    fn add(self, rhs: usize) -> SeqNumber {
+        SeqNumber(self.0.wrapping_add(rhs as u32))
+    }
+}
+
+impl std::ops::Sub<SeqNumber> for SeqNumber {
+    type Output = usize;
+
+    /// Returns the forward distance from `rhs` to `self`, i.e. how many
+    /// bytes `rhs` would need to advance by to reach `self`.
+    ///
+    /// Panics if `self` is actually behind `rhs` (true underflow), not
+    /// merely wrapped ahead of it.
+    fn sub(self, rhs: SeqNumber) -> usize {
+        let diff = self.0.wrapping_sub(rhs.0);
+        assert!(diff as i32 >= 0, "SeqNumber subtraction underflow");
+        diff as usize
+    }
+}
+
+impl SeqNumber {
+    /// Forward distance from `self` to `other`, saturating to zero instead
+    /// of panicking if `other` isn't actually ahead - useful when the two
+    /// numbers come from untrusted/possibly-malformed captured traffic.
+    fn distance_to(&self, other: SeqNumber) -> usize {
+        if other >= *self {
+            other - *self
+        } else {
+            0
+        }
+    }
+}
 
 #[derive(Clone, Copy)]
 struct TcpHostSeq {
     /// Most recent sequence number that the host was sent
-    seq_no: u32,
+    seq_no: SeqNumber,
     /// Expected next sequence number to send to the host
-    next_seq_no: u32,
+    next_seq_no: SeqNumber,
     /// Most recent acknowledgment number that the host sent
-    ack_no: u32,
+    ack_no: SeqNumber,
     /// Advertised window size of the host
     window_size: u16,
-    /// Window scale option advertised by the host
-    window_scale: u8,
+    /// Window scale option advertised by the host's SYN, if any
+    window_scale: Option<u8>,
+}
+
+impl TcpHostSeq {
+    /// This host's effective receive window, honoring RFC 1323 window
+    /// scaling. Scaling only applies once both sides of the handshake
+    /// advertised the option - if either omitted it, the window stays
+    /// unscaled even when this host did advertise a scale factor.
+    fn effective_window(&self, peer_window_scale: Option<u8>) -> u32 {
+        match (self.window_scale, peer_window_scale) {
+            (Some(scale), Some(_)) => (self.window_size as u32) << scale,
+            _ => self.window_size as u32,
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -22,52 +102,263 @@ pub enum TcpState {
     SynSent {
         client_window_size: u16,
         /// Window scale option advertised by the client
-        client_window_scale: u8,
+        client_window_scale: Option<u8>,
         /// Most recent sequence number sent to the server
-        server_seq_no: u32,
+        server_seq_no: SeqNumber,
         /// Expected next sequence number to send to the server
-        server_next_seq_no: u32,
+        server_next_seq_no: SeqNumber,
     },
     /// State after seeing SYN-ACK from server
     SynReceived {
         /// Most recent sequence number sent to the server
-        server_seq_no: u32,
+        server_seq_no: SeqNumber,
         /// Expected next sequence number to send to the server
-        server_next_seq_no: u32,
+        server_next_seq_no: SeqNumber,
         /// Most recent acknowledgment number sent to the client
-        server_ack_no: u32,
+        server_ack_no: SeqNumber,
         /// Advertised window size of the server
         server_window_size: u16,
         /// Window scale option advertised by the server
-        server_window_scale: u8,
+        server_window_scale: Option<u8>,
 
         /// Most recent sequence number sent to the client
-        client_seq_no: u32,
+        client_seq_no: SeqNumber,
         /// Expected next sequence number to send to the client
-        client_next_seq_no: u32,
+        client_next_seq_no: SeqNumber,
         /// Advertised window size of the client
         client_window_size: u16,
         /// Window scale option advertised by the client
-        client_window_scale: u8,
+        client_window_scale: Option<u8>,
     },
     /// State after seeing ACK from client
     Established {
         client_seq: TcpHostSeq,
         server_seq: TcpHostSeq,
     },
-    /// State after seeing FIN from client
-    /// Waiting for ACK-FIN from server
-    ClientFin,
-    /// State after seeing FIN from server
-    /// Waiting for ACK-FIN from client
-    ServerFin,
-    /// State after seeing ACK-FIN from server/client
-    /// Waiting for last ACK from client/server and TCP FIN timeout
-    Closing,
-    /// State after TCP FIN timeout has expired
+    /// The `closer` has sent a FIN that has not yet been acked.
+    FinWait1 {
+        /// Side that sent the first FIN (the active closer).
+        closer: PacketDirection,
+        client_seq: TcpHostSeq,
+        server_seq: TcpHostSeq,
+    },
+    /// The closer's FIN has been acked and the peer has gone quiet;
+    /// waiting for the peer's own FIN.
+    FinWait2 {
+        closer: PacketDirection,
+        client_seq: TcpHostSeq,
+        server_seq: TcpHostSeq,
+    },
+    /// The closer's FIN has been acked but the peer is still sending data
+    /// on its still-open half of the connection.
+    CloseWait {
+        closer: PacketDirection,
+        client_seq: TcpHostSeq,
+        server_seq: TcpHostSeq,
+    },
+    /// Simultaneous close: both sides have sent FIN and one has been
+    /// acked; waiting for the other to be acked too.
+    LastAck {
+        closer: PacketDirection,
+        client_seq: TcpHostSeq,
+        server_seq: TcpHostSeq,
+    },
+    /// Simultaneous close: both sides have sent FIN but neither has acked
+    /// the other's yet.
+    Closing {
+        closer: PacketDirection,
+        client_seq: TcpHostSeq,
+        server_seq: TcpHostSeq,
+    },
+    /// Both FINs have been observed and the first one acked; wait 2*MSL so trailing
+    /// retransmitted FINs/ACKs are still attributed to this session
+    /// before it is torn down.
+    TimeWait {
+        client_seq: TcpHostSeq,
+        server_seq: TcpHostSeq,
+        entered_at: u64,
+    },
+    /// The 2*MSL TIME_WAIT window has elapsed.
     Closed,
 }
 
+/// Maximum Segment Lifetime assumption used to size the TIME_WAIT window, in
+/// milliseconds like every other timestamp in this crate (see
+/// `DEFAULT_TCP_IDLE_TIMEOUT`/`ingress`'s `ts_sec * 1000 + ts_usec / 1000`).
+/// Real stacks use values between 30s and 120s; this uses 60s.
+const MSL: u64 = 60_000;
+
+/// Round-trip time estimate for a host, smoothed with the Jacobson/Karels
+/// algorithm (RFC 6298) from TCP timestamp-echo samples.
+#[derive(Clone, Copy)]
+struct TcpHostRtt {
+    /// This host's most recently observed (timestamp value, arrival instant),
+    /// kept until the peer echoes it back so the elapsed time can be sampled.
+    last_ts_sent: Option<(u32, u64)>,
+    /// Smoothed round-trip time (SRTT), in the same units as the timestamps
+    /// passed into `TcpSession::process_packet`.
+    srtt: Option<u32>,
+    /// Smoothed round-trip time variance (RTTVAR).
+    rttvar: Option<u32>,
+}
+
+impl TcpHostRtt {
+    fn new() -> Self {
+        Self {
+            last_ts_sent: None,
+            srtt: None,
+            rttvar: None,
+        }
+    }
+
+    fn add_sample(&mut self, sample: u32) {
+        match (self.srtt, self.rttvar) {
+            (Some(srtt), Some(rttvar)) => {
+                let delta = (srtt as i64 - sample as i64).unsigned_abs() as u32;
+                self.rttvar = Some((3 * rttvar + delta) / 4);
+                self.srtt = Some((7 * srtt + sample) / 8);
+            }
+            _ => {
+                self.srtt = Some(sample);
+                self.rttvar = Some(sample / 2);
+            }
+        }
+    }
+
+    /// Retransmission timeout estimate: `SRTT + 4*RTTVAR`.
+    #[inline]
+    fn rto(&self) -> Option<u32> {
+        match (self.srtt, self.rttvar) {
+            (Some(srtt), Some(rttvar)) => Some(srtt + 4 * rttvar),
+            _ => None,
+        }
+    }
+}
+
+/// Running min/max/count/sum for a round-trip-style latency metric, kept
+/// instead of every sample so an average can still be recovered as
+/// `sum / count`.
+#[derive(Clone, Copy, Default)]
+struct LatencyStats {
+    count: u64,
+    sum: u64,
+    min: Option<u32>,
+    max: Option<u32>,
+}
+
+impl LatencyStats {
+    fn record(&mut self, sample: u32) {
+        self.count += 1;
+        self.sum += sample as u64;
+        self.min = Some(self.min.map_or(sample, |m| m.min(sample)));
+        self.max = Some(self.max.map_or(sample, |m| m.max(sample)));
+    }
+}
+
+/// Maximum number of distinct gaps an `Assembler` tracks at once. Beyond
+/// this the stream is fragmented enough that `out_of_order_count` already
+/// shows the reassembly pressure, so further holes are simply not recorded
+/// individually rather than reimplementing a full reassembly buffer.
+const ASSEMBLER_CAPACITY: usize = 16;
+
+/// Outcome of recording a byte range with an `Assembler`.
+enum AssemblerEvent {
+    /// Already covered by a previously recorded range.
+    Duplicate,
+    /// Extended an existing interval (or was the first range recorded);
+    /// no gap was opened or closed.
+    Extended,
+    /// Landed apart from every recorded interval, opening a new gap.
+    OutOfOrder,
+    /// Bridged two or more recorded intervals, closing a gap.
+    Recovered,
+}
+
+/// Tracks which byte ranges of a host's TCP stream have been observed, as
+/// a sorted list of disjoint, non-adjacent `(start, len)` intervals that
+/// are merged on insert. This is enough to notice out-of-order segments
+/// and gaps that later get filled in, without reassembling the stream's
+/// actual contents.
+#[derive(Clone, Copy)]
+struct Assembler {
+    intervals: [(SeqNumber, usize); ASSEMBLER_CAPACITY],
+    count: usize,
+}
+
+impl Assembler {
+    fn new() -> Self {
+        Self {
+            intervals: [(SeqNumber::new(0), 0); ASSEMBLER_CAPACITY],
+            count: 0,
+        }
+    }
+
+    /// Number of gaps between recorded intervals, i.e. outstanding
+    /// reassembly holes in this stream.
+    fn hole_count(&self) -> usize {
+        self.count.saturating_sub(1)
+    }
+
+    /// Records that `[start, start + len)` has been observed.
+    fn insert(&mut self, start: SeqNumber, len: usize) -> AssemblerEvent {
+        if len == 0 {
+            return AssemblerEvent::Duplicate;
+        }
+        let end = start + len;
+
+        for i in 0..self.count {
+            let (i_start, i_len) = self.intervals[i];
+            if i_start <= start && end <= i_start + i_len {
+                return AssemblerEvent::Duplicate;
+            }
+        }
+
+        let was_empty = self.count == 0;
+
+        // Intervals are sorted and disjoint, so the ones this segment
+        // touches or overlaps form one contiguous run [lo, hi).
+        let mut lo = 0;
+        while lo < self.count && self.intervals[lo].0 + self.intervals[lo].1 < start {
+            lo += 1;
+        }
+        let mut hi = lo;
+        while hi < self.count && self.intervals[hi].0 <= end {
+            hi += 1;
+        }
+        let touched = hi - lo;
+
+        if touched == 0 {
+            if self.count >= self.intervals.len() {
+                return AssemblerEvent::OutOfOrder;
+            }
+            for i in (lo..self.count).rev() {
+                self.intervals[i + 1] = self.intervals[i];
+            }
+            self.intervals[lo] = (start, len);
+            self.count += 1;
+            return if was_empty {
+                AssemblerEvent::Extended
+            } else {
+                AssemblerEvent::OutOfOrder
+            };
+        }
+
+        let merged_start = start.min(self.intervals[lo].0);
+        let merged_end = end.max(self.intervals[hi - 1].0 + self.intervals[hi - 1].1);
+        self.intervals[lo] = (merged_start, merged_end - merged_start);
+        for i in hi..self.count {
+            self.intervals[lo + 1 + (i - hi)] = self.intervals[i];
+        }
+        self.count -= touched - 1;
+
+        if touched >= 2 {
+            AssemblerEvent::Recovered
+        } else {
+            AssemblerEvent::Extended
+        }
+    }
+}
+
 pub struct TcpHostStats {
     syn_count: u8,
     syn_rexmit_count: u8,
@@ -77,6 +368,37 @@ pub struct TcpHostStats {
 
     rexmit_count: u32,
     rexmit_bytes: usize,
+
+    /// Most recent ack number sent by this host, used to spot duplicate acks.
+    local_rx_last_ack: Option<SeqNumber>,
+    /// Window advertised alongside `local_rx_last_ack`.
+    local_rx_last_window: u16,
+    /// Consecutive duplicate acks seen since the ack number last advanced.
+    local_rx_dup_acks: u32,
+    /// Number of times this host's duplicate acks crossed the fast-retransmit
+    /// threshold (three consecutive duplicate acks).
+    fast_rexmit_count: u32,
+
+    /// Round-trip time estimate derived from this host's TCP timestamps.
+    rtt: TcpHostRtt,
+
+    /// Number of segments this host sent while advertising a zero
+    /// (scaling-adjusted) receive window.
+    zero_window_count: u32,
+    /// Number of segments from the peer that showed this host's
+    /// unacknowledged data filling the peer's advertised receive window,
+    /// i.e. the peer's window was full.
+    window_full_count: u32,
+
+    /// Tracks which byte ranges of this host's stream have been observed,
+    /// to detect out-of-order segments and gaps that later get filled in.
+    assembler: Assembler,
+    /// Number of segments that landed ahead of the contiguous front,
+    /// leaving a reassembly gap behind them.
+    out_of_order_count: u32,
+    /// Number of segments (or peer-reported SACK ranges) that filled a
+    /// previously-recorded reassembly gap.
+    recovered_count: u32,
 }
 
 impl TcpHostStats {
@@ -88,8 +410,56 @@ impl TcpHostStats {
             rst_rexmit_count: 0,
             rexmit_count: 0,
             rexmit_bytes: 0,
+            local_rx_last_ack: None,
+            local_rx_last_window: 0,
+            local_rx_dup_acks: 0,
+            fast_rexmit_count: 0,
+            rtt: TcpHostRtt::new(),
+            zero_window_count: 0,
+            window_full_count: 0,
+            assembler: Assembler::new(),
+            out_of_order_count: 0,
+            recovered_count: 0,
         }
     }
+
+    /// This host's retransmit/window/reassembly counters, for a monitoring
+    /// caller that wants these mid-session rather than waiting for
+    /// `TcpSession::info()`'s end-of-connection summary.
+    fn stats(&self) -> TcpHostPerfStats {
+        TcpHostPerfStats {
+            retransmit_count: self.rexmit_count,
+            retransmit_bytes: self.rexmit_bytes,
+            dup_ack_count: self.local_rx_dup_acks,
+            fast_retransmit_count: self.fast_rexmit_count,
+            rto: self.rtt.rto(),
+            zero_window_count: self.zero_window_count,
+            window_full_count: self.window_full_count,
+            hole_count: self.assembler.hole_count(),
+        }
+    }
+}
+
+/// A point-in-time snapshot of one direction's TCP performance counters -
+/// retransmits, duplicate acks, RTO, window exhaustion, and outstanding
+/// reassembly holes - for a monitoring caller that wants these mid-session
+/// rather than waiting for `TcpSession::info()`'s end-of-connection summary.
+#[derive(Debug, Clone, Copy)]
+pub struct TcpHostPerfStats {
+    pub retransmit_count: u32,
+    pub retransmit_bytes: usize,
+    /// Consecutive duplicate acks seen since the ack number last advanced.
+    pub dup_ack_count: u32,
+    /// Number of times this host's duplicate acks crossed the
+    /// fast-retransmit threshold (three consecutive duplicate acks).
+    pub fast_retransmit_count: u32,
+    /// Retransmission timeout estimate (`SRTT + 4*RTTVAR`), or `None`
+    /// until this host's timestamps have yielded at least one RTT sample.
+    pub rto: Option<u32>,
+    pub zero_window_count: u32,
+    pub window_full_count: u32,
+    /// Number of outstanding reassembly gaps in this host's stream.
+    pub hole_count: usize,
 }
 
 pub struct TcpSession {
@@ -97,6 +467,21 @@ pub struct TcpSession {
     server_stats: TcpHostStats,
 
     state: TcpState,
+
+    /// When the client's SYN was seen, kept until the server's SYN-ACK
+    /// arrives to sample the handshake's network round-trip time.
+    syn_sent_at: Option<u64>,
+    /// Handshake RTT: the elapsed time between the client's SYN and the
+    /// server's SYN-ACK - the only full network round trip the three-way
+    /// handshake offers a sample of.
+    connection_rtt: Option<u32>,
+
+    /// When the most recent client data segment was seen, kept until the
+    /// server's first reply segment to sample server response time.
+    pending_request_at: Option<u64>,
+    /// Server response time: from the last client data segment of a
+    /// request burst to the first server data segment that answers it.
+    server_response_time: LatencyStats,
 }
 
 impl TcpSession {
@@ -105,17 +490,22 @@ impl TcpSession {
             client_stats: TcpHostStats::new(),
             server_stats: TcpHostStats::new(),
             state: TcpState::Listen,
+            syn_sent_at: None,
+            connection_rtt: None,
+            pending_request_at: None,
+            server_response_time: LatencyStats::default(),
         }
     }
 
-    fn process_listen(&mut self, tcp_input: &TcpPacketInput, options: &[TcpOption]) {
+    fn process_listen(&mut self, tcp_input: &TcpPacketInput, options: &[TcpOption], now: u64) {
         if tcp_input.syn() {
             self.state = TcpState::SynSent {
                 client_window_size: tcp_input.window,
-                client_window_scale: 0,
+                client_window_scale: window_scale_option(options),
                 server_seq_no: tcp_input.seq_no,
-                server_next_seq_no: tcp_input.seq_no + 1,
+                server_next_seq_no: tcp_input.seq_no + 1usize,
             };
+            self.syn_sent_at = Some(now);
         }
 
         if tcp_input.syn() {
@@ -126,60 +516,488 @@ impl TcpSession {
         }
     }
 
-    fn process_syn_sent(&mut self, tcp_input: &TcpPacketInput, options: &[TcpOption]) {
+    fn process_syn_sent(&mut self, tcp_input: &TcpPacketInput, options: &[TcpOption], now: u64) {
         let ack_no = tcp_input.ack_no();
 
+        let TcpState::SynSent {
+            client_window_size,
+            client_window_scale,
+            ..
+        } = self.state
+        else {
+            return;
+        };
+
         if ack_no.is_some() && tcp_input.syn() {
             // SYN-ACK packet from server
+            if let Some(syn_sent_at) = self.syn_sent_at.take() {
+                self.connection_rtt = Some(now.saturating_sub(syn_sent_at) as u32);
+            }
+
             self.state = TcpState::SynReceived {
                 server_seq_no: tcp_input.seq_no,
-                server_next_seq_no: tcp_input.seq_no + 1,
+                server_next_seq_no: tcp_input.seq_no + 1usize,
                 server_ack_no: ack_no.unwrap(),
                 server_window_size: tcp_input.window,
-                server_window_scale: 0,
+                server_window_scale: window_scale_option(options),
 
-                client_seq_no: 0,
-                client_next_seq_no: 0,
-                client_window_size: 0,
-                client_window_scale: 0,
+                client_seq_no: SeqNumber::new(0),
+                client_next_seq_no: SeqNumber::new(0),
+                client_window_size,
+                client_window_scale,
             };
         }
     }
 
-    fn process_syn_received(&mut self, tcp_input: &TcpPacketInput, options: &[TcpOption]) {
+    fn process_syn_received(&mut self, tcp_input: &TcpPacketInput, _options: &[TcpOption]) {
         let ack_no = tcp_input.ack_no();
 
-        if ack_no.is_some() && tcp_input.syn() {
-            // SYN-ACK packet from server
+        let TcpState::SynReceived {
+            server_seq_no,
+            server_next_seq_no,
+            server_ack_no,
+            server_window_size,
+            server_window_scale,
+            client_window_size,
+            client_window_scale,
+            ..
+        } = self.state
+        else {
+            return;
+        };
+
+        // The final ACK of the handshake carries no SYN, unlike the
+        // SYN-ACK that got us into this state.
+        if ack_no.is_some() && !tcp_input.syn() {
             self.state = TcpState::Established {
                 client_seq: TcpHostSeq {
                     seq_no: tcp_input.seq_no,
-                    next_seq_no: tcp_input.seq_no + 1,
+                    next_seq_no: tcp_input.seq_no + tcp_input.payload_len,
                     ack_no: ack_no.unwrap(),
-                    window_size: tcp_input.window,
-                    window_scale: 0,
+                    window_size: client_window_size,
+                    window_scale: client_window_scale,
                 },
                 server_seq: TcpHostSeq {
-                    seq_no: tcp_input.seq_no,
-                    next_seq_no: tcp_input.seq_no + 1,
-                    ack_no: ack_no.unwrap(),
-                    window_size: tcp_input.window,
-                    window_scale: 0,
+                    seq_no: server_seq_no,
+                    next_seq_no: server_next_seq_no,
+                    ack_no: server_ack_no,
+                    window_size: server_window_size,
+                    window_scale: server_window_scale,
                 },
             };
         }
     }
 
-    fn process_client_fin(&mut self, tcp_input: &TcpPacketInput) {}
+    /// Records an observed `[start, start + len)` byte range against
+    /// `stats`'s assembler, bumping `out_of_order_count`/`recovered_count`
+    /// as the resulting reassembly gaps open or close.
+    fn record_segment(stats: &mut TcpHostStats, start: SeqNumber, len: usize) {
+        match stats.assembler.insert(start, len) {
+            AssemblerEvent::OutOfOrder => stats.out_of_order_count += 1,
+            AssemblerEvent::Recovered => stats.recovered_count += 1,
+            AssemblerEvent::Extended | AssemblerEvent::Duplicate => {}
+        }
+    }
+
+    /// Advances `seq`'s tracking to reflect a segment sent by its host,
+    /// without the retransmit/dup-ack bookkeeping `process_established`
+    /// does - teardown traffic is low-volume enough that simple forward
+    /// tracking is sufficient to tell when each side's FIN has been acked.
+    fn advance_teardown_seq(seq: &mut TcpHostSeq, tcp_input: &TcpPacketInput) {
+        if tcp_input.seq_no >= seq.next_seq_no {
+            let mut advance = tcp_input.payload_len;
+            if tcp_input.syn() || tcp_input.fin() {
+                advance += 1;
+            }
+            seq.next_seq_no = tcp_input.seq_no + advance;
+        }
+        seq.seq_no = tcp_input.seq_no;
+        if let Some(ack_no) = tcp_input.ack_no() {
+            seq.ack_no = ack_no;
+        }
+    }
+
+    fn process_fin_wait1(&mut self, tcp_input: &TcpPacketInput, now: u64) {
+        let TcpState::FinWait1 {
+            closer,
+            mut client_seq,
+            mut server_seq,
+        } = self.state
+        else {
+            return;
+        };
+
+        let sender_seq = match tcp_input.direction {
+            PacketDirection::ClientToServer => &mut client_seq,
+            PacketDirection::ServerToClient => &mut server_seq,
+        };
+        Self::advance_teardown_seq(sender_seq, tcp_input);
+
+        self.state = if tcp_input.direction == closer {
+            // The closer retransmitting its FIN or trailing data - still
+            // waiting on the peer either way.
+            TcpState::FinWait1 {
+                closer,
+                client_seq,
+                server_seq,
+            }
+        } else {
+            let closer_seq = match closer {
+                PacketDirection::ClientToServer => client_seq,
+                PacketDirection::ServerToClient => server_seq,
+            };
+            let acked_our_fin = tcp_input
+                .ack_no()
+                .is_some_and(|ack| ack >= closer_seq.next_seq_no);
+
+            match (tcp_input.fin(), acked_our_fin) {
+                (true, true) => TcpState::TimeWait {
+                    client_seq,
+                    server_seq,
+                    entered_at: now,
+                },
+                (true, false) => TcpState::Closing {
+                    closer,
+                    client_seq,
+                    server_seq,
+                },
+                (false, true) if tcp_input.payload_len > 0 => TcpState::CloseWait {
+                    closer,
+                    client_seq,
+                    server_seq,
+                },
+                (false, true) => TcpState::FinWait2 {
+                    closer,
+                    client_seq,
+                    server_seq,
+                },
+                (false, false) => TcpState::FinWait1 {
+                    closer,
+                    client_seq,
+                    server_seq,
+                },
+            }
+        };
+    }
+
+    fn process_fin_wait2(&mut self, tcp_input: &TcpPacketInput, now: u64) {
+        let TcpState::FinWait2 {
+            closer,
+            mut client_seq,
+            mut server_seq,
+        } = self.state
+        else {
+            return;
+        };
+
+        let sender_seq = match tcp_input.direction {
+            PacketDirection::ClientToServer => &mut client_seq,
+            PacketDirection::ServerToClient => &mut server_seq,
+        };
+        Self::advance_teardown_seq(sender_seq, tcp_input);
 
-    fn process_server_fin(&mut self, tcp_input: &TcpPacketInput) {}
+        self.state = if tcp_input.direction != closer && tcp_input.fin() {
+            TcpState::TimeWait {
+                client_seq,
+                server_seq,
+                entered_at: now,
+            }
+        } else if tcp_input.direction != closer && tcp_input.payload_len > 0 {
+            TcpState::CloseWait {
+                closer,
+                client_seq,
+                server_seq,
+            }
+        } else {
+            TcpState::FinWait2 {
+                closer,
+                client_seq,
+                server_seq,
+            }
+        };
+    }
 
-    fn process_closing(&mut self, tcp_input: &TcpPacketInput) {}
+    fn process_close_wait(&mut self, tcp_input: &TcpPacketInput, now: u64) {
+        let TcpState::CloseWait {
+            closer,
+            mut client_seq,
+            mut server_seq,
+        } = self.state
+        else {
+            return;
+        };
+
+        let sender_seq = match tcp_input.direction {
+            PacketDirection::ClientToServer => &mut client_seq,
+            PacketDirection::ServerToClient => &mut server_seq,
+        };
+        Self::advance_teardown_seq(sender_seq, tcp_input);
+
+        self.state = if tcp_input.direction != closer && tcp_input.fin() {
+            TcpState::TimeWait {
+                client_seq,
+                server_seq,
+                entered_at: now,
+            }
+        } else if tcp_input.direction != closer && tcp_input.payload_len == 0 {
+            // The peer went quiet without sending its FIN yet.
+            TcpState::FinWait2 {
+                closer,
+                client_seq,
+                server_seq,
+            }
+        } else {
+            TcpState::CloseWait {
+                closer,
+                client_seq,
+                server_seq,
+            }
+        };
+    }
+
+    fn process_closing(&mut self, tcp_input: &TcpPacketInput) {
+        let TcpState::Closing {
+            closer,
+            mut client_seq,
+            mut server_seq,
+        } = self.state
+        else {
+            return;
+        };
+
+        let sender_seq = match tcp_input.direction {
+            PacketDirection::ClientToServer => &mut client_seq,
+            PacketDirection::ServerToClient => &mut server_seq,
+        };
+        Self::advance_teardown_seq(sender_seq, tcp_input);
+
+        let other_seq = match tcp_input.direction {
+            PacketDirection::ClientToServer => server_seq,
+            PacketDirection::ServerToClient => client_seq,
+        };
+        let acked_other_fin = tcp_input
+            .ack_no()
+            .is_some_and(|ack| ack >= other_seq.next_seq_no);
+
+        self.state = if acked_other_fin {
+            TcpState::LastAck {
+                closer,
+                client_seq,
+                server_seq,
+            }
+        } else {
+            TcpState::Closing {
+                closer,
+                client_seq,
+                server_seq,
+            }
+        };
+    }
+
+    fn process_last_ack(&mut self, tcp_input: &TcpPacketInput, now: u64) {
+        let TcpState::LastAck {
+            closer,
+            mut client_seq,
+            mut server_seq,
+        } = self.state
+        else {
+            return;
+        };
+
+        let sender_seq = match tcp_input.direction {
+            PacketDirection::ClientToServer => &mut client_seq,
+            PacketDirection::ServerToClient => &mut server_seq,
+        };
+        Self::advance_teardown_seq(sender_seq, tcp_input);
+
+        let other_seq = match tcp_input.direction {
+            PacketDirection::ClientToServer => server_seq,
+            PacketDirection::ServerToClient => client_seq,
+        };
+        let acked_other_fin = tcp_input
+            .ack_no()
+            .is_some_and(|ack| ack >= other_seq.next_seq_no);
+
+        self.state = if acked_other_fin {
+            TcpState::TimeWait {
+                client_seq,
+                server_seq,
+                entered_at: now,
+            }
+        } else {
+            TcpState::LastAck {
+                closer,
+                client_seq,
+                server_seq,
+            }
+        };
+    }
+
+    /// Waits 2*MSL from `entered_at` before fully closing, so trailing
+    /// retransmitted FINs/ACKs that arrive late are still attributed to
+    /// this session rather than starting a new one.
+    fn process_time_wait(&mut self, now: u64) {
+        let TcpState::TimeWait { entered_at, .. } = self.state else {
+            return;
+        };
+
+        if now.saturating_sub(entered_at) >= 2 * MSL {
+            self.state = TcpState::Closed;
+        }
+    }
 
     #[inline]
-    fn process_established(&mut self, tcp_input: &TcpPacketInput, sack_ranges: &[TcpSackRange]) {}
+    fn process_established(
+        &mut self,
+        tcp_input: &TcpPacketInput,
+        sack_ranges: &[TcpSackRange],
+        now: u64,
+    ) {
+        let TcpState::Established {
+            mut client_seq,
+            mut server_seq,
+        } = self.state
+        else {
+            return;
+        };
+
+        let (sender_seq, peer_seq, sender_stats, peer_stats) = match tcp_input.direction {
+            PacketDirection::ClientToServer => (
+                &mut client_seq,
+                &server_seq,
+                &mut self.client_stats,
+                &mut self.server_stats,
+            ),
+            PacketDirection::ServerToClient => (
+                &mut server_seq,
+                &client_seq,
+                &mut self.server_stats,
+                &mut self.client_stats,
+            ),
+        };
+
+        let payload_len = tcp_input.payload_len;
+        let is_behind = tcp_input.seq_no < sender_seq.next_seq_no;
+        let behind_by = if is_behind {
+            sender_seq.next_seq_no - tcp_input.seq_no
+        } else {
+            0
+        };
+        // A keepalive probe resends a single stale byte purely to elicit an
+        // ACK; it isn't evidence of loss, so don't count it as a retransmit.
+        let is_keepalive_probe = payload_len == 1 && behind_by == 1;
+
+        if is_behind {
+            if tcp_input.syn() {
+                sender_stats.syn_rexmit_count += 1;
+            } else if tcp_input.rst() {
+                sender_stats.rst_rexmit_count += 1;
+            } else if payload_len > 0 && !is_keepalive_probe {
+                sender_stats.rexmit_count += 1;
+                sender_stats.rexmit_bytes += payload_len;
+            }
+        } else {
+            let mut advance = payload_len;
+            if tcp_input.syn() || tcp_input.fin() {
+                advance += 1;
+            }
+            sender_seq.next_seq_no = tcp_input.seq_no + advance;
+        }
+
+        sender_seq.seq_no = tcp_input.seq_no;
+        sender_seq.window_size = tcp_input.window;
+
+        if let Some(ack_no) = tcp_input.ack_no() {
+            let is_dup = payload_len == 0
+                && sender_stats.local_rx_last_ack == Some(ack_no)
+                && sender_stats.local_rx_last_window == tcp_input.window;
+
+            if is_dup {
+                sender_stats.local_rx_dup_acks += 1;
+                if sender_stats.local_rx_dup_acks == 3 {
+                    sender_stats.fast_rexmit_count += 1;
+                }
+            } else if sender_stats.local_rx_last_ack != Some(ack_no) {
+                // Ack number advanced - the duplicate-ack run is over.
+                sender_stats.local_rx_dup_acks = 0;
+            }
+
+            sender_stats.local_rx_last_ack = Some(ack_no);
+            sender_stats.local_rx_last_window = tcp_input.window;
+            sender_seq.ack_no = ack_no;
+        }
+
+        // This host's advertised receive window, and whether the peer is
+        // sending right up against it.
+        let effective_window = sender_seq.effective_window(peer_seq.window_scale);
+        if effective_window == 0 {
+            sender_stats.zero_window_count += 1;
+        }
+
+        let peer_in_flight = sender_seq.ack_no.distance_to(peer_seq.next_seq_no);
+        if effective_window > 0 && peer_in_flight >= effective_window as usize {
+            peer_stats.window_full_count += 1;
+        }
+
+        if let Some(ts) = tcp_input.timestamp {
+            if let Some((echoed_value, sent_at)) = peer_stats.rtt.last_ts_sent {
+                if echoed_value == ts.echo() {
+                    let sample = now.saturating_sub(sent_at) as u32;
+                    peer_stats.rtt.add_sample(sample);
+                    peer_stats.rtt.last_ts_sent = None;
+                }
+            }
+
+            sender_stats.rtt.last_ts_sent = Some((ts.timestamp(), now));
+        }
+
+        if payload_len > 0 {
+            Self::record_segment(sender_stats, tcp_input.seq_no, payload_len);
+        }
+
+        // SACK blocks describe ranges of the peer's stream that this host
+        // has already received out of order, so they fill the peer's
+        // assembler directly - this is what lets a genuinely reordered
+        // segment get marked recovered even if it was dropped by the
+        // capture itself.
+        for sack in sack_ranges {
+            let start = SeqNumber::new(sack.start());
+            let len = start.distance_to(SeqNumber::new(sack.end()));
+            Self::record_segment(peer_stats, start, len);
+        }
+
+        // Server response time: the last client data segment of a request
+        // burst marks when the request finished; the server's first reply
+        // segment after that closes the sample.
+        if payload_len > 0 {
+            match tcp_input.direction {
+                PacketDirection::ClientToServer => {
+                    self.pending_request_at = Some(now);
+                }
+                PacketDirection::ServerToClient => {
+                    if let Some(request_at) = self.pending_request_at.take() {
+                        self.server_response_time
+                            .record(now.saturating_sub(request_at) as u32);
+                    }
+                }
+            }
+        }
+
+        self.state = if tcp_input.fin() {
+            TcpState::FinWait1 {
+                closer: tcp_input.direction,
+                client_seq,
+                server_seq,
+            }
+        } else {
+            TcpState::Established {
+                client_seq,
+                server_seq,
+            }
+        };
+    }
 
-    pub fn process_packet(&mut self, buffer: &[u8], direction: PacketDirection) {
+    pub fn process_packet(&mut self, buffer: &[u8], direction: PacketDirection, now: u64) {
         let mut sack_ranges = [TcpSackRange(0, 0); 4];
         let mut options = [TcpOption::NoOp; 4];
 
@@ -191,32 +1009,83 @@ impl TcpSession {
 
         match self.state.clone() {
             TcpState::Listen => {
-                self.process_listen(&tcp_input, options_param);
+                self.process_listen(&tcp_input, options_param, now);
             }
             TcpState::SynSent { .. } => {
-                self.process_syn_sent(&tcp_input, options_param);
+                self.process_syn_sent(&tcp_input, options_param, now);
             }
             TcpState::SynReceived { .. } => {
                 self.process_syn_received(&tcp_input, options_param);
             }
             TcpState::Established { .. } => {
-                self.process_established(&tcp_input, sack_ranges_param);
+                self.process_established(&tcp_input, sack_ranges_param, now);
             }
-            TcpState::ClientFin => {
-                self.process_client_fin(&tcp_input);
+            TcpState::FinWait1 { .. } => {
+                self.process_fin_wait1(&tcp_input, now);
             }
-            TcpState::ServerFin => {
-                self.process_server_fin(&tcp_input);
+            TcpState::FinWait2 { .. } => {
+                self.process_fin_wait2(&tcp_input, now);
             }
-            TcpState::Closing => {
+            TcpState::CloseWait { .. } => {
+                self.process_close_wait(&tcp_input, now);
+            }
+            TcpState::Closing { .. } => {
                 self.process_closing(&tcp_input);
             }
+            TcpState::LastAck { .. } => {
+                self.process_last_ack(&tcp_input, now);
+            }
+            TcpState::TimeWait { .. } => {
+                self.process_time_wait(now);
+            }
             TcpState::Closed => {
                 // Session is closed, ignore any further packets
             }
-            _ => {}
         };
     }
+
+    /// The client's retransmit/window/reassembly counters, for a monitoring
+    /// caller that wants more than `info()`'s connection-level summary -
+    /// mirrors `UdpSession::client_rtp_stats`.
+    pub fn client_perf_stats(&self) -> TcpHostPerfStats {
+        self.client_stats.stats()
+    }
+
+    /// The server's retransmit/window/reassembly counters, mirroring
+    /// `UdpSession::server_rtp_stats`.
+    pub fn server_perf_stats(&self) -> TcpHostPerfStats {
+        self.server_stats.stats()
+    }
+
+    /// Whether the RFC 793 teardown has fully completed, so a caller like
+    /// `FlowMonShard::reap` can evict this session without waiting for another
+    /// packet to arrive and drive the state machine forward: either the
+    /// last packet processed already pushed it to `Closed`, or it's
+    /// sitting in `TimeWait` and the `2*MSL` window has elapsed on its own
+    /// since then.
+    pub fn is_closed(&self, now: u64) -> bool {
+        match self.state {
+            TcpState::Closed => true,
+            TcpState::TimeWait { entered_at, .. } => now.saturating_sub(entered_at) >= 2 * MSL,
+            _ => false,
+        }
+    }
+
+    /// Surfaces handshake RTT and/or server-response-time stats as
+    /// `SessionInfo`, once there's anything to report.
+    pub fn info(&self) -> Option<SessionInfo> {
+        if self.connection_rtt.is_none() && self.server_response_time.count == 0 {
+            return None;
+        }
+
+        Some(SessionInfo::Tcp {
+            connection_rtt: self.connection_rtt,
+            response_time_count: self.server_response_time.count,
+            response_time_sum: self.server_response_time.sum,
+            response_time_min: self.server_response_time.min,
+            response_time_max: self.server_response_time.max,
+        })
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -275,10 +1144,21 @@ enum TcpOption {
     SackPermitted,
 }
 
+/// Scans a SYN's parsed options for a window scale, returning `None` if it
+/// wasn't advertised - scaling is only in effect once both sides of the
+/// handshake advertise it, so "not present" has to be distinguishable from
+/// "advertised as zero".
+fn window_scale_option(options: &[TcpOption]) -> Option<u8> {
+    options.iter().find_map(|option| match option {
+        TcpOption::WindowScale { scale } => Some(*scale),
+        _ => None,
+    })
+}
+
 struct TcpPacketInput {
     direction: PacketDirection,
-    seq_no: u32,
-    ack_no: u32,
+    seq_no: SeqNumber,
+    ack_no: SeqNumber,
     window: u16,
     urgent: u16,
     flags: u8,
@@ -286,6 +1166,7 @@ struct TcpPacketInput {
     timestamp: Option<TcpTimestamp>,
     option_count: u8,
     payload_offset: u8,
+    payload_len: usize,
 }
 
 impl TcpPacketInput {
@@ -295,8 +1176,8 @@ impl TcpPacketInput {
         sack_ranges: &mut [TcpSackRange],
         options: &mut [TcpOption],
     ) -> Self {
-        let seq_no = u32::from_be_bytes([buffer[4], buffer[5], buffer[6], buffer[7]]);
-        let ack_no = u32::from_be_bytes([buffer[8], buffer[9], buffer[10], buffer[11]]);
+        let seq_no = SeqNumber::new(u32::from_be_bytes([buffer[4], buffer[5], buffer[6], buffer[7]]));
+        let ack_no = SeqNumber::new(u32::from_be_bytes([buffer[8], buffer[9], buffer[10], buffer[11]]));
 
         let data_offset = 4 * (buffer[12] >> 4);
         let flags = buffer[13];
@@ -426,6 +1307,8 @@ impl TcpPacketInput {
             }
         }
 
+        let payload_len = buffer.len().saturating_sub(data_offset as usize);
+
         Self {
             direction,
             seq_no,
@@ -437,11 +1320,12 @@ impl TcpPacketInput {
             timestamp,
             option_count,
             payload_offset: data_offset,
+            payload_len,
         }
     }
 
     #[inline]
-    fn ack_no(&self) -> Option<u32> {
+    fn ack_no(&self) -> Option<SeqNumber> {
         self.ack().then_some(self.ack_no)
     }
 
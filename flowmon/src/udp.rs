@@ -1,15 +1,393 @@
-use crate::PacketDirection;
+use crate::{PacketDirection, SessionInfo};
 
-pub struct UdpHostStats {}
+/// A point-in-time snapshot of one direction's RFC 3550 receiver metrics,
+/// for a monitoring caller that wants call-quality numbers mid-session
+/// rather than waiting for the session to end.
+#[derive(Debug, Clone, Copy)]
+pub struct RtpStats {
+    pub ssrc: u32,
+    pub payload_type: u8,
+    pub packets_received: u64,
+    pub octets_received: u64,
+    pub packets_lost: i64,
+    pub jitter: f64,
+    pub out_of_order: u64,
+    pub duplicates: u64,
+}
+
+/// Per-direction counters for one host in a UDP flow. Once RTP is seen in
+/// this direction, `rtp_stats` exposes the RFC 3550 §A receiver metrics
+/// for it.
+pub struct UdpHostStats {
+    rtp: RtpStreamStats,
+}
 
 impl UdpHostStats {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            rtp: RtpStreamStats::default(),
+        }
+    }
+
+    fn observe_rtp(&mut self, header: &RtpHeader, arrival: u64, payload_len: usize) {
+        self.rtp.observe(header, arrival, payload_len);
+    }
+
+    fn apply_remote_rtcp_report(&mut self, report: &RtcpReport) {
+        self.rtp.apply_remote_report(report);
+    }
+
+    /// This direction's RTP call-quality metrics, or `None` if no RTP has
+    /// been seen here yet.
+    pub fn rtp_stats(&self) -> Option<RtpStats> {
+        self.rtp.snapshot()
     }
 }
 
 pub enum UdpState {
+    /// Nothing recognizable has been seen on this flow yet.
     Init,
+    /// At least one STUN message has been seen (e.g. an ICE connectivity
+    /// check) but no media has started flowing.
+    StunConnectivityCheck,
+    /// RTP has been observed in at least one direction.
+    Established,
+}
+
+/// Which protocol a UDP payload belongs to, per the first-byte/length
+/// disambiguation scheme RFC 7983 defines for multiplexing STUN, TURN,
+/// DTLS and RTP/RTCP onto one 5-tuple.
+enum PacketKind {
+    Stun,
+    TurnChannelData,
+    Rtp,
+    Rtcp,
+    Sip,
+    Unknown,
+}
+
+fn classify(buffer: &[u8]) -> PacketKind {
+    let Some(&first_byte) = buffer.first() else {
+        return PacketKind::Unknown;
+    };
+
+    match first_byte {
+        0..=3 if StunHeader::has_valid_cookie(buffer) => PacketKind::Stun,
+        64..=79 => PacketKind::TurnChannelData,
+        128..=191 => {
+            let packet_type = buffer.get(1).copied().unwrap_or(0);
+            if (192..=223).contains(&packet_type) {
+                PacketKind::Rtcp
+            } else {
+                PacketKind::Rtp
+            }
+        }
+        _ if looks_like_sip(buffer) => PacketKind::Sip,
+        _ => PacketKind::Unknown,
+    }
+}
+
+/// A crude textual sniff for SIP: either a status line (`SIP/2.0 ...`) or a
+/// request line whose method token is all-uppercase ASCII, the way every
+/// SIP method (including extension methods by convention) is written.
+fn looks_like_sip(buffer: &[u8]) -> bool {
+    let Ok(text) = std::str::from_utf8(buffer) else {
+        return false;
+    };
+
+    let first_line = text.split(['\r', '\n']).next().unwrap_or("");
+    if first_line.starts_with("SIP/2.0") {
+        return true;
+    }
+
+    match first_line.split(' ').next() {
+        Some(token) => !token.is_empty() && token.chars().all(|c| c.is_ascii_uppercase()),
+        None => false,
+    }
+}
+
+/// A STUN message header (RFC 8489 §5): enough of it - the fixed magic
+/// cookie at a known offset - to confirm a buffer in the 0-3 first-byte
+/// range is really STUN rather than something else landing there by
+/// coincidence.
+struct StunHeader;
+
+impl StunHeader {
+    const MAGIC_COOKIE: u32 = 0x2112A442;
+    const HEADER_LEN: usize = 20;
+
+    fn has_valid_cookie(buffer: &[u8]) -> bool {
+        buffer.len() >= Self::HEADER_LEN
+            && u32::from_be_bytes(buffer[4..8].try_into().unwrap()) == Self::MAGIC_COOKIE
+    }
+}
+
+/// A TURN ChannelData header (RFC 8656 §12.4): a channel number followed
+/// by the relayed payload's length, with no magic cookie to validate -
+/// the 64-79 first-byte range is the only signal this is TURN.
+struct TurnChannelData {
+    channel_number: u16,
+    length: u16,
+}
+
+impl TurnChannelData {
+    const HEADER_LEN: usize = 4;
+
+    fn parse(buffer: &[u8]) -> Option<Self> {
+        if buffer.len() < Self::HEADER_LEN {
+            return None;
+        }
+
+        Some(Self {
+            channel_number: u16::from_be_bytes(buffer[0..2].try_into().ok()?),
+            length: u16::from_be_bytes(buffer[2..4].try_into().ok()?),
+        })
+    }
+}
+
+/// RTP payload types RFC 3550 §5.1 reserves to avoid ambiguity with the
+/// RTCP packet types 200-204, which share the same version bits; a packet
+/// claiming one of these isn't a "sensible" RTP payload type.
+const RESERVED_PAYLOAD_TYPES: std::ops::RangeInclusive<u8> = 72..=76;
+
+/// A parsed RTP fixed header (RFC 3550 §5.1), ignoring the optional CSRC
+/// list and header extension - this module only needs the fields that
+/// drive loss/jitter accounting.
+struct RtpHeader {
+    payload_type: u8,
+    sequence_number: u16,
+    timestamp: u32,
+    ssrc: u32,
+}
+
+impl RtpHeader {
+    const LEN: usize = 12;
+
+    fn parse(buffer: &[u8]) -> Option<Self> {
+        if buffer.len() < Self::LEN {
+            return None;
+        }
+
+        if buffer[0] >> 6 != 2 {
+            return None;
+        }
+
+        let payload_type = buffer[1] & 0x7F;
+        if RESERVED_PAYLOAD_TYPES.contains(&payload_type) {
+            return None;
+        }
+
+        Some(Self {
+            payload_type,
+            sequence_number: u16::from_be_bytes([buffer[2], buffer[3]]),
+            timestamp: u32::from_be_bytes(buffer[4..8].try_into().ok()?),
+            ssrc: u32::from_be_bytes(buffer[8..12].try_into().ok()?),
+        })
+    }
+}
+
+/// A Sender or Receiver Report pulled out of an RTCP compound packet (RFC
+/// 3550 §6.4) - just the first report block, which is all a 1:1 RTP
+/// session needs to find out what the far end thinks loss/jitter look
+/// like.
+pub struct RtcpReport {
+    source_ssrc: u32,
+    cumulative_lost: i32,
+    jitter: u32,
+}
+
+impl RtcpReport {
+    const SENDER_REPORT: u8 = 200;
+    const RECEIVER_REPORT: u8 = 201;
+    const SENDER_INFO_LEN: usize = 20;
+    const REPORT_BLOCK_LEN: usize = 24;
+
+    fn parse(buffer: &[u8]) -> Option<Self> {
+        if buffer.len() < 8 {
+            return None;
+        }
+
+        if buffer[0] >> 6 != 2 {
+            return None;
+        }
+
+        let report_count = buffer[0] & 0x1F;
+        let packet_type = buffer[1];
+
+        let report_block_offset = match packet_type {
+            Self::SENDER_REPORT => 8 + Self::SENDER_INFO_LEN,
+            Self::RECEIVER_REPORT => 8,
+            _ => return None,
+        };
+
+        if report_count == 0 || buffer.len() < report_block_offset + Self::REPORT_BLOCK_LEN {
+            return None;
+        }
+
+        let block = &buffer[report_block_offset..report_block_offset + Self::REPORT_BLOCK_LEN];
+        let source_ssrc = u32::from_be_bytes(block[0..4].try_into().ok()?);
+
+        // Cumulative packets lost is a signed 24-bit field; sign-extend it
+        // into an i32 from its top bit.
+        let sign_byte = if block[5] & 0x80 != 0 { 0xFF } else { 0x00 };
+        let cumulative_lost = i32::from_be_bytes([sign_byte, block[5], block[6], block[7]]);
+
+        let jitter = u32::from_be_bytes(block[12..16].try_into().ok()?);
+
+        Some(Self {
+            source_ssrc,
+            cumulative_lost,
+            jitter,
+        })
+    }
+}
+
+/// A remote-reported loss/jitter pair, pulled from an `RtcpReport` on the
+/// paired RTCP flow once its source SSRC matches a stream this session is
+/// tracking. Preferred over the locally-estimated figures in `info()`
+/// since it's what the receiver itself measured, rather than our guess
+/// from watching packets fly by.
+struct RemoteReport {
+    cumulative_lost: i32,
+    jitter: u32,
+}
+
+/// How many of the most recently seen extended sequence numbers are kept
+/// around to tell a duplicate (exact repeat) apart from a merely
+/// out-of-order packet (an earlier-but-never-seen sequence number).
+const REORDER_WINDOW: usize = 64;
+
+/// Loss, jitter, and ordering tracking for one direction of an RTP stream,
+/// per RFC 3550 §A.
+#[derive(Default)]
+struct RtpStreamStats {
+    ssrc: Option<u32>,
+    payload_type: Option<u8>,
+    packets_received: u64,
+    octets_received: u64,
+    base_seq: Option<u16>,
+    highest_seq: u16,
+    cycles: u32,
+    last_timestamp: Option<u32>,
+    last_arrival: Option<u64>,
+    jitter: f64,
+    remote_report: Option<RemoteReport>,
+    recent_sequences: std::collections::VecDeque<i64>,
+    out_of_order: u64,
+    duplicates: u64,
+}
+
+impl RtpStreamStats {
+    /// The 16-bit sequence number extended with the count of times it has
+    /// wrapped, so ordering and the expected-packet count stay correct
+    /// across a stream's lifetime instead of just one 65536-sequence cycle.
+    fn extend(cycles: u32, seq: u16) -> i64 {
+        ((cycles as i64) << 16) | seq as i64
+    }
+
+    fn observe(&mut self, header: &RtpHeader, arrival: u64, payload_len: usize) {
+        self.payload_type = Some(header.payload_type);
+        self.packets_received += 1;
+        self.octets_received += payload_len as u64;
+
+        let is_first = self.base_seq.is_none();
+        // Captured under the cycle count `highest_seq` was itself recorded
+        // under, before `cycles` is possibly bumped below - otherwise a
+        // wrap would re-extend the stale `highest_seq` under the new
+        // cycle count and compare it against itself one cycle too high.
+        let previous_extended = Self::extend(self.cycles, self.highest_seq);
+
+        match self.base_seq {
+            None => {
+                self.base_seq = Some(header.sequence_number);
+                self.highest_seq = header.sequence_number;
+                self.ssrc = Some(header.ssrc);
+            }
+            Some(_) => {
+                // A big backward jump in the raw 16-bit sequence number
+                // means it wrapped around, not that packets arrived out
+                // of order by tens of thousands.
+                if header.sequence_number < self.highest_seq
+                    && self.highest_seq - header.sequence_number > 0x8000
+                {
+                    self.cycles += 1;
+                }
+            }
+        }
+
+        let extended = Self::extend(self.cycles, header.sequence_number);
+        if is_first {
+            self.highest_seq = header.sequence_number;
+        } else if extended > previous_extended {
+            self.highest_seq = header.sequence_number;
+        } else if self.recent_sequences.contains(&extended) {
+            self.duplicates += 1;
+        } else {
+            self.out_of_order += 1;
+        }
+        self.track_recent(extended);
+
+        // RFC 3550 §A.8: J += (|D| - J) / 16, where D is the difference
+        // between consecutive packets' RTP-timestamp delta and their
+        // wall-clock arrival delta.
+        if let (Some(last_timestamp), Some(last_arrival)) = (self.last_timestamp, self.last_arrival) {
+            let timestamp_delta = header.timestamp.wrapping_sub(last_timestamp) as i64;
+            let arrival_delta = arrival.wrapping_sub(last_arrival) as i64;
+            let d = (arrival_delta - timestamp_delta).abs() as f64;
+            self.jitter += (d - self.jitter) / 16.0;
+        }
+
+        self.last_timestamp = Some(header.timestamp);
+        self.last_arrival = Some(arrival);
+    }
+
+    fn track_recent(&mut self, extended: i64) {
+        if self.recent_sequences.len() == REORDER_WINDOW {
+            self.recent_sequences.pop_front();
+        }
+        self.recent_sequences.push_back(extended);
+    }
+
+    /// Cumulative packets lost: how many of the sequence numbers between
+    /// the first and the highest seen were never received.
+    fn packets_lost(&self) -> i64 {
+        let Some(base_seq) = self.base_seq else {
+            return 0;
+        };
+
+        let expected = Self::extend(self.cycles, self.highest_seq) - base_seq as i64 + 1;
+        expected - self.packets_received as i64
+    }
+
+    fn apply_remote_report(&mut self, report: &RtcpReport) {
+        if self.ssrc == Some(report.source_ssrc) {
+            self.remote_report = Some(RemoteReport {
+                cumulative_lost: report.cumulative_lost,
+                jitter: report.jitter,
+            });
+        }
+    }
+
+    fn snapshot(&self) -> Option<RtpStats> {
+        let ssrc = self.ssrc?;
+        let payload_type = self.payload_type?;
+
+        let (packets_lost, jitter) = match &self.remote_report {
+            Some(remote) => (remote.cumulative_lost as i64, remote.jitter as f64),
+            None => (self.packets_lost(), self.jitter),
+        };
+
+        Some(RtpStats {
+            ssrc,
+            payload_type,
+            packets_received: self.packets_received,
+            octets_received: self.octets_received,
+            packets_lost,
+            jitter,
+            out_of_order: self.out_of_order,
+            duplicates: self.duplicates,
+        })
+    }
 }
 
 pub struct UdpSession {
@@ -28,10 +406,105 @@ impl UdpSession {
         }
     }
 
-    pub fn process_packet(&mut self, buffer: &[u8], direction: PacketDirection) {
-        match direction {
-            PacketDirection::ClientToServer => {}
-            PacketDirection::ServerToClient => {}
+    /// Classifies one UDP payload per RFC 7983 and routes it to the
+    /// matching protocol handler. An RTCP Sender/Receiver Report is handed
+    /// back to the caller rather than applied here, since it describes a
+    /// stream on the *paired* RTP flow - a different `UdpSession` on the
+    /// even port directly below - which only `FlowMonShard` has visibility
+    /// into.
+    pub fn process_packet(
+        &mut self,
+        buffer: &[u8],
+        direction: PacketDirection,
+        now: u64,
+    ) -> Option<RtcpReport> {
+        match classify(buffer) {
+            PacketKind::Stun => {
+                self.handle_stun();
+                None
+            }
+            PacketKind::TurnChannelData => {
+                self.handle_turn_channel_data(buffer);
+                None
+            }
+            PacketKind::Rtp => {
+                self.handle_rtp(buffer, direction, now);
+                None
+            }
+            PacketKind::Rtcp => RtcpReport::parse(buffer),
+            PacketKind::Sip => {
+                self.handle_sip();
+                None
+            }
+            PacketKind::Unknown => None,
+        }
+    }
+
+    /// An ICE connectivity check (or any other STUN transaction) moves a
+    /// fresh flow out of `Init`, but doesn't itself count as established
+    /// media - that's reserved for RTP.
+    fn handle_stun(&mut self) {
+        if matches!(self.state, UdpState::Init) {
+            self.state = UdpState::StunConnectivityCheck;
         }
     }
+
+    /// Unwrapping the RTP/RTCP this typically relays is future work; for
+    /// now this just confirms the envelope is well-formed so a TURN flow
+    /// isn't mistaken for `Unknown`.
+    fn handle_turn_channel_data(&mut self, buffer: &[u8]) {
+        let _ = TurnChannelData::parse(buffer);
+    }
+
+    fn handle_rtp(&mut self, buffer: &[u8], direction: PacketDirection, now: u64) {
+        let Some(header) = RtpHeader::parse(buffer) else {
+            return;
+        };
+        let payload_len = buffer.len().saturating_sub(RtpHeader::LEN);
+
+        let stats = match direction {
+            PacketDirection::ClientToServer => &mut self.client_stats,
+            PacketDirection::ServerToClient => &mut self.server_stats,
+        };
+        stats.observe_rtp(&header, now, payload_len);
+        self.state = UdpState::Established;
+    }
+
+    /// Recognizing in-dialog SIP signaling interleaved with its media is
+    /// future work; for now this only confirms the classification.
+    fn handle_sip(&mut self) {}
+
+    /// Applies an RTCP report pulled from the paired RTCP flow to whichever
+    /// of this session's RTP streams it's actually reporting on.
+    pub fn apply_remote_rtcp_report(&mut self, report: &RtcpReport) {
+        self.client_stats.apply_remote_rtcp_report(report);
+        self.server_stats.apply_remote_rtcp_report(report);
+    }
+
+    /// This direction's RTP call-quality metrics, for a monitoring caller
+    /// that wants more than `info()`'s single-stream summary - loss,
+    /// jitter, and reordering mid-session, not just at teardown.
+    pub fn client_rtp_stats(&self) -> Option<RtpStats> {
+        self.client_stats.rtp_stats()
+    }
+
+    pub fn server_rtp_stats(&self) -> Option<RtpStats> {
+        self.server_stats.rtp_stats()
+    }
+
+    /// The busier of the two directions' RTP streams, surfaced as
+    /// `SessionInfo::Rtp`, or `None` if neither has seen a valid RTP
+    /// packet yet.
+    pub fn info(&self) -> Option<SessionInfo> {
+        [self.client_stats.rtp_stats(), self.server_stats.rtp_stats()]
+            .into_iter()
+            .flatten()
+            .max_by_key(|stats| stats.packets_received)
+            .map(|stats| SessionInfo::Rtp {
+                payload_type: stats.payload_type,
+                packets_lost: stats.packets_lost,
+                jitter: stats.jitter,
+                ssrc: stats.ssrc,
+            })
+    }
 }
@@ -26,6 +26,82 @@ STUN - https://datatracker.ietf.org/doc/html/rfc8489
 
 */
 
+use std::fmt;
+
+/// Why a `Msg`/`Uri`/`SipUri` parse attempt failed. Carries a byte offset
+/// where that's meaningful, so a fuzzer or diagnostic caller can point
+/// straight at the offending bytes instead of re-deriving the location.
+#[derive(Debug)]
+pub enum SipParseError {
+    /// The buffer ends before a complete start line and header block could
+    /// be found - not garbage, just incomplete. A streaming caller should
+    /// buffer more bytes and retry rather than give up.
+    Truncated,
+    /// The start line and headers contain bytes that are not valid UTF-8.
+    /// Unlike `Truncated`, this is not resumable - the bytes up to
+    /// `offset` are already malformed, so no amount of additional data
+    /// will fix it and a caller should give up on the message rather than
+    /// retry.
+    InvalidEncoding { offset: usize },
+    MalformedStartLine { offset: usize },
+    MalformedHeader { offset: usize },
+    UnknownMethod { offset: usize, token: String },
+    InvalidStatusCode(u16),
+    InvalidUri { reason: String },
+    MissingRequiredHeader(&'static str),
+    HeaderTooLong { offset: usize },
+    /// `Content-Length` declared more bytes than are actually available -
+    /// also resumable, since the rest of the body may simply not have
+    /// arrived yet.
+    BodyLengthMismatch { declared: usize, actual: usize },
+}
+
+impl fmt::Display for SipParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SipParseError::Truncated => write!(f, "message is incomplete, need more bytes"),
+            SipParseError::InvalidEncoding { offset } => write!(
+                f,
+                "start line/headers are not valid UTF-8 at offset {}",
+                offset
+            ),
+            SipParseError::MalformedStartLine { offset } => {
+                write!(f, "malformed start line at offset {}", offset)
+            }
+            SipParseError::MalformedHeader { offset } => {
+                write!(f, "malformed header at offset {}", offset)
+            }
+            SipParseError::UnknownMethod { offset, token } => {
+                write!(f, "unrecognized method {:?} at offset {}", token, offset)
+            }
+            SipParseError::InvalidStatusCode(code) => {
+                write!(f, "status code {} is outside the valid 100-699 range", code)
+            }
+            SipParseError::InvalidUri { reason } => write!(f, "invalid URI: {}", reason),
+            SipParseError::MissingRequiredHeader(name) => {
+                write!(f, "missing required header {}", name)
+            }
+            SipParseError::HeaderTooLong { offset } => write!(
+                f,
+                "header line at offset {} exceeds the {}-byte limit",
+                offset, MAX_HEADER_LINE_LEN
+            ),
+            SipParseError::BodyLengthMismatch { declared, actual } => write!(
+                f,
+                "Content-Length declared {} bytes but only {} are available",
+                declared, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SipParseError {}
+
+/// Header lines past this length are rejected outright rather than grown
+/// without bound, the same defensive posture the ingress path takes with
+/// capture buffers.
+const MAX_HEADER_LINE_LEN: usize = 8192;
+
 pub struct SipUri {
     pub user: Option<String>,
     pub password: Option<String>,
@@ -35,6 +111,111 @@ pub struct SipUri {
     pub headers: Vec<(String, String)>,
 }
 
+impl SipUri {
+    /// Parses the part of a `sip:`/`sips:` URI after the scheme, per RFC
+    /// 3261 §25.1 / RFC 2396: `user[:password]@host[:port]` followed by
+    /// any number of `;name[=value]` params and a `?name=value&...` header
+    /// block.
+    pub fn parse(s: &str) -> Result<SipUri, SipParseError> {
+        let (before_headers, headers_part) = match s.split_once('?') {
+            Some((before, rest)) => (before, Some(rest)),
+            None => (s, None),
+        };
+
+        let (user_host, params_part) = match before_headers.split_once(';') {
+            Some((uh, rest)) => (uh, Some(rest)),
+            None => (before_headers, None),
+        };
+
+        let (userinfo, host_port) = match user_host.split_once('@') {
+            Some((ui, hp)) => (Some(ui), hp),
+            None => (None, user_host),
+        };
+
+        let (user, password) = match userinfo {
+            Some(ui) => match ui.split_once(':') {
+                Some((user, password)) => (Some(user.to_string()), Some(password.to_string())),
+                None => (Some(ui.to_string()), None),
+            },
+            None => (None, None),
+        };
+
+        let (host, port) = match host_port.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port.parse().map_err(|_| SipParseError::InvalidUri {
+                    reason: format!("{:?} is not a valid port", port),
+                })?;
+                (host.to_string(), Some(port))
+            }
+            None => (host_port.to_string(), None),
+        };
+        if host.is_empty() {
+            return Err(SipParseError::InvalidUri {
+                reason: "empty host".to_string(),
+            });
+        }
+
+        let params = parse_key_value_list(params_part, ';', '=');
+        let headers = parse_key_value_list(headers_part, '&', '=');
+
+        Ok(SipUri {
+            user,
+            password,
+            host,
+            port,
+            params,
+            headers,
+        })
+    }
+}
+
+impl fmt::Display for SipUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(user) = &self.user {
+            write!(f, "{}", user)?;
+            if let Some(password) = &self.password {
+                write!(f, ":{}", password)?;
+            }
+            write!(f, "@")?;
+        }
+
+        write!(f, "{}", self.host)?;
+        if let Some(port) = self.port {
+            write!(f, ":{}", port)?;
+        }
+
+        for (name, value) in &self.params {
+            if value.is_empty() {
+                write!(f, ";{}", name)?;
+            } else {
+                write!(f, ";{}={}", name, value)?;
+            }
+        }
+
+        for (i, (name, value)) in self.headers.iter().enumerate() {
+            write!(f, "{}{}={}", if i == 0 { "?" } else { "&" }, name, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits a `;`- or `&`-delimited parameter/header list into `name=value`
+/// pairs, treating a param with no `=` as a valueless flag.
+fn parse_key_value_list(part: Option<&str>, separator: char, assign: char) -> Vec<(String, String)> {
+    let Some(part) = part else {
+        return Vec::new();
+    };
+
+    part.split(separator)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once(assign) {
+            Some((name, value)) => (name.to_string(), value.to_string()),
+            None => (entry.to_string(), String::new()),
+        })
+        .collect()
+}
+
 pub enum Uri {
     Sip(SipUri),
     Sips(SipUri),
@@ -44,10 +225,175 @@ pub enum Uri {
     },
 }
 
+impl Uri {
+    /// Dispatches on the URI scheme, parsing `sip:`/`sips:` into a
+    /// `SipUri` and falling through to `Other` for anything else (`tel:`,
+    /// `http:`, ...) rather than rejecting it outright.
+    pub fn parse(s: &str) -> Result<Uri, SipParseError> {
+        if let Some(rest) = s.strip_prefix("sips:") {
+            return SipUri::parse(rest).map(Uri::Sips);
+        }
+        if let Some(rest) = s.strip_prefix("sip:") {
+            return SipUri::parse(rest).map(Uri::Sip);
+        }
+
+        let (schema, schema_specific_part) = s.split_once(':').ok_or_else(|| SipParseError::InvalidUri {
+            reason: format!("{:?} has no scheme", s),
+        })?;
+        Ok(Uri::Other {
+            schema: schema.to_string(),
+            schema_specific_part: schema_specific_part.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Uri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Uri::Sip(uri) => write!(f, "sip:{}", uri),
+            Uri::Sips(uri) => write!(f, "sips:{}", uri),
+            Uri::Other {
+                schema,
+                schema_specific_part,
+            } => write!(f, "{}:{}", schema, schema_specific_part),
+        }
+    }
+}
+
 pub struct Msg {
     pub start_line: StartLine,
     pub headers: Vec<Header>,
-    pub body: Option<String>,
+    /// Raw body bytes, sized by `Content-Length`. Not required to be valid
+    /// UTF-8 - an SDP body can carry binary attachments - so unlike the
+    /// start line and headers this is kept as bytes rather than `String`.
+    pub body: Option<Vec<u8>>,
+}
+
+impl Msg {
+    /// Parses a full SIP message: a start line, headers (folding
+    /// continuation lines per RFC 3261 §7.3.1 onto the header above them),
+    /// a blank line, and a body sized by `Content-Length`. Only the start
+    /// line and headers are required to be valid UTF-8; the body is kept
+    /// as raw bytes so a non-text payload doesn't fail the parse.
+    pub fn parse(data: &[u8]) -> Result<Msg, SipParseError> {
+        let (head_bytes, body_start) = split_head_and_body(data).ok_or(SipParseError::Truncated)?;
+        let head = std::str::from_utf8(head_bytes)
+            .map_err(|e| SipParseError::InvalidEncoding { offset: e.valid_up_to() })?;
+
+        let mut lines = lines_with_offsets(head);
+        let (start_offset, start_text) = lines.next().ok_or(SipParseError::Truncated)?;
+        let start_line = StartLine::parse(start_text, start_offset)?;
+
+        let mut headers: Vec<Header> = Vec::new();
+        for (offset, line) in lines {
+            if line.is_empty() {
+                continue;
+            }
+            if line.len() > MAX_HEADER_LINE_LEN {
+                return Err(SipParseError::HeaderTooLong { offset });
+            }
+
+            if line.starts_with(' ') || line.starts_with('\t') {
+                if let Some(last) = headers.last_mut() {
+                    last.value.push(' ');
+                    last.value.push_str(line.trim());
+                }
+                continue;
+            }
+
+            headers.push(Header::parse(line, offset)?);
+        }
+
+        let content_length = headers
+            .iter()
+            .find(|header| header.name.eq_ignore_ascii_case("Content-Length"))
+            .and_then(|header| header.value.trim().parse::<usize>().ok());
+
+        let rest = &data[body_start..];
+        let body = match content_length {
+            Some(0) => None,
+            Some(len) if len > rest.len() => {
+                return Err(SipParseError::BodyLengthMismatch {
+                    declared: len,
+                    actual: rest.len(),
+                })
+            }
+            Some(len) => Some(rest[..len].to_vec()),
+            None if rest.is_empty() => None,
+            None => Some(rest.to_vec()),
+        };
+
+        Ok(Msg {
+            start_line,
+            headers,
+            body,
+        })
+    }
+
+    /// Serializes back to the exact bytes a compliant peer would send,
+    /// body included - unlike `Display`, which renders the body lossily
+    /// as text for diagnostics.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = format!("{}\r\n", self.start_line).into_bytes();
+        for header in &self.headers {
+            out.extend_from_slice(format!("{}: {}\r\n", header.name, header.value).as_bytes());
+        }
+        out.extend_from_slice(b"\r\n");
+        if let Some(body) = &self.body {
+            out.extend_from_slice(body);
+        }
+        out
+    }
+}
+
+impl fmt::Display for Msg {
+    /// Renders the message for diagnostics. The body, if present, is
+    /// decoded lossily (invalid UTF-8 becomes U+FFFD) since `Display`
+    /// produces text - use `to_bytes` for an exact, binary-safe rendering.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\r\n", self.start_line)?;
+        for header in &self.headers {
+            write!(f, "{}: {}\r\n", header.name, header.value)?;
+        }
+        write!(f, "\r\n")?;
+        if let Some(body) = &self.body {
+            write!(f, "{}", String::from_utf8_lossy(body))?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the blank line separating headers from the body, returning the
+/// header bytes and the byte offset the body starts at. Tolerates bare
+/// `\n` line endings alongside the RFC 3261 `\r\n`. `None` if no blank
+/// line has arrived yet - the message may simply be incomplete. Operates
+/// on raw bytes, not `&str`, since a binary body shouldn't stop the
+/// header block ahead of it from being found.
+fn split_head_and_body(data: &[u8]) -> Option<(&[u8], usize)> {
+    if let Some(idx) = find_subslice(data, b"\r\n\r\n") {
+        return Some((&data[..idx], idx + 4));
+    }
+    if let Some(idx) = find_subslice(data, b"\n\n") {
+        return Some((&data[..idx], idx + 2));
+    }
+    None
+}
+
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// Splits header text into `(byte offset, line)` pairs, stripping each
+/// line's trailing `\r` so offsets stay anchored to the original buffer
+/// even though lines themselves are compared without it.
+fn lines_with_offsets(head: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut offset = 0;
+    head.split('\n').map(move |raw_line| {
+        let line = raw_line.strip_suffix('\r').unwrap_or(raw_line);
+        let line_offset = offset;
+        offset += raw_line.len() + 1;
+        (line_offset, line)
+    })
 }
 
 pub enum StartLine {
@@ -55,6 +401,29 @@ pub enum StartLine {
     Response(Status),
 }
 
+impl StartLine {
+    /// A response start line always begins with the SIP version
+    /// (`SIP/2.0 200 OK`); a request start line never does
+    /// (`INVITE sip:bob@example.com SIP/2.0`), so that prefix is enough to
+    /// tell them apart.
+    fn parse(line: &str, offset: usize) -> Result<StartLine, SipParseError> {
+        if line.starts_with("SIP/") {
+            Status::parse(line, offset).map(StartLine::Response)
+        } else {
+            Request::parse(line, offset).map(StartLine::Request)
+        }
+    }
+}
+
+impl fmt::Display for StartLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartLine::Request(request) => write!(f, "{}", request),
+            StartLine::Response(status) => write!(f, "{}", status),
+        }
+    }
+}
+
 pub enum Method {
     Register,
     Invite,
@@ -142,62 +511,104 @@ pub enum StatusCode {
     Decline,
     DoesNotExistAnywhere,
     NotAcceptable606,
+    /// A well-formed but unrecognized status code, kept around verbatim so
+    /// it can be proxied or re-serialized rather than dropped.
+    Unregistered(u16),
+}
+
+/// The RFC 3261 §21 status-code class, derived from a code's leading digit.
+/// Lets a caller that doesn't recognize a specific code (a vendor 299, a
+/// future 5xx) still react to its broad category.
+pub enum StatusClass {
+    Provisional,
+    Success,
+    Redirection,
+    ClientError,
+    ServerError,
+    GlobalFailure,
 }
 
 impl StatusCode {
-    pub fn from_code(code: u16) -> Option<StatusCode> {
+    /// This code's class, derived from its leading digit rather than from
+    /// which variant it is, so it works for `Unregistered` codes too.
+    pub fn class(&self) -> StatusClass {
+        match self.to_code() / 100 {
+            1 => StatusClass::Provisional,
+            2 => StatusClass::Success,
+            3 => StatusClass::Redirection,
+            4 => StatusClass::ClientError,
+            5 => StatusClass::ServerError,
+            _ => StatusClass::GlobalFailure,
+        }
+    }
+
+    /// The x00 representative of this code's class (100/200/300/400/500/600),
+    /// for callers that want to degrade an unrecognized code to something
+    /// they know how to handle.
+    pub fn default_code(&self) -> StatusCode {
+        match self.class() {
+            StatusClass::Provisional => StatusCode::Trying,
+            StatusClass::Success => StatusCode::Ok,
+            StatusClass::Redirection => StatusCode::MultipleChoices,
+            StatusClass::ClientError => StatusCode::BadRequest,
+            StatusClass::ServerError => StatusCode::ServerInternalError,
+            StatusClass::GlobalFailure => StatusCode::BusyEverywhere,
+        }
+    }
+
+    pub fn from_code(code: u16) -> StatusCode {
         match code {
-            100 => Some(StatusCode::Trying),
-            180 => Some(StatusCode::Ringing),
-            181 => Some(StatusCode::CallBeingForwarded),
-            182 => Some(StatusCode::Queued),
-            183 => Some(StatusCode::SessionProgress),
-            200 => Some(StatusCode::Ok),
-            300 => Some(StatusCode::MultipleChoices),
-            301 => Some(StatusCode::MovedPermanently),
-            302 => Some(StatusCode::MovedTemporarily),
-            305 => Some(StatusCode::UseProxy),
-            380 => Some(StatusCode::AlternativeService),
-            400 => Some(StatusCode::BadRequest),
-            401 => Some(StatusCode::Unauthorized),
-            402 => Some(StatusCode::PaymentRequired),
-            403 => Some(StatusCode::Forbidden),
-            404 => Some(StatusCode::NotFound),
-            405 => Some(StatusCode::MethodNotAllowed),
-            406 => Some(StatusCode::NotAcceptable406),
-            407 => Some(StatusCode::ProxyAuthenticationRequired),
-            408 => Some(StatusCode::RequestTimeout),
-            410 => Some(StatusCode::Gone),
-            413 => Some(StatusCode::RequestEntityTooLarge),
-            414 => Some(StatusCode::RequestUriTooLong),
-            415 => Some(StatusCode::UnsupportedMediaType),
-            416 => Some(StatusCode::UnsupportedUriScheme),
-            420 => Some(StatusCode::BadExtension),
-            421 => Some(StatusCode::ExtensionRequired),
-            423 => Some(StatusCode::IntervalTooBrief),
-            480 => Some(StatusCode::TemporarilyUnavailable),
-            481 => Some(StatusCode::CallOrTransactionDoesNotExist),
-            482 => Some(StatusCode::LoopDetected),
-            483 => Some(StatusCode::TooManyHops),
-            484 => Some(StatusCode::AddressIncomplete),
-            485 => Some(StatusCode::Ambiguous),
-            486 => Some(StatusCode::BusyHere),
-            487 => Some(StatusCode::RequestTerminated),
-            488 => Some(StatusCode::NotAcceptableHere),
-            491 => Some(StatusCode::RequestPending),
-            493 => Some(StatusCode::Undecipherable),
-            500 => Some(StatusCode::ServerInternalError),
-            501 => Some(StatusCode::NotImplemented),
-            502 => Some(StatusCode::BadGateway),
-            503 => Some(StatusCode::ServiceUnavailable),
-            504 => Some(StatusCode::ServerTimeout),
-            505 => Some(StatusCode::VersionNotSupported),
-            513 => Some(StatusCode::MessageTooLarge),
-            600 => Some(StatusCode::BusyEverywhere),
-            603 => Some(StatusCode::Decline),
-            604 => Some(StatusCode::DoesNotExistAnywhere),
-            606 => Some(StatusCode::NotAcceptable606),
-            _ => None,
+            100 => StatusCode::Trying,
+            180 => StatusCode::Ringing,
+            181 => StatusCode::CallBeingForwarded,
+            182 => StatusCode::Queued,
+            183 => StatusCode::SessionProgress,
+            200 => StatusCode::Ok,
+            300 => StatusCode::MultipleChoices,
+            301 => StatusCode::MovedPermanently,
+            302 => StatusCode::MovedTemporarily,
+            305 => StatusCode::UseProxy,
+            380 => StatusCode::AlternativeService,
+            400 => StatusCode::BadRequest,
+            401 => StatusCode::Unauthorized,
+            402 => StatusCode::PaymentRequired,
+            403 => StatusCode::Forbidden,
+            404 => StatusCode::NotFound,
+            405 => StatusCode::MethodNotAllowed,
+            406 => StatusCode::NotAcceptable406,
+            407 => StatusCode::ProxyAuthenticationRequired,
+            408 => StatusCode::RequestTimeout,
+            410 => StatusCode::Gone,
+            413 => StatusCode::RequestEntityTooLarge,
+            414 => StatusCode::RequestUriTooLong,
+            415 => StatusCode::UnsupportedMediaType,
+            416 => StatusCode::UnsupportedUriScheme,
+            420 => StatusCode::BadExtension,
+            421 => StatusCode::ExtensionRequired,
+            423 => StatusCode::IntervalTooBrief,
+            480 => StatusCode::TemporarilyUnavailable,
+            481 => StatusCode::CallOrTransactionDoesNotExist,
+            482 => StatusCode::LoopDetected,
+            483 => StatusCode::TooManyHops,
+            484 => StatusCode::AddressIncomplete,
+            485 => StatusCode::Ambiguous,
+            486 => StatusCode::BusyHere,
+            487 => StatusCode::RequestTerminated,
+            488 => StatusCode::NotAcceptableHere,
+            491 => StatusCode::RequestPending,
+            493 => StatusCode::Undecipherable,
+            500 => StatusCode::ServerInternalError,
+            501 => StatusCode::NotImplemented,
+            502 => StatusCode::BadGateway,
+            503 => StatusCode::ServiceUnavailable,
+            504 => StatusCode::ServerTimeout,
+            505 => StatusCode::VersionNotSupported,
+            513 => StatusCode::MessageTooLarge,
+            600 => StatusCode::BusyEverywhere,
+            603 => StatusCode::Decline,
+            604 => StatusCode::DoesNotExistAnywhere,
+            606 => StatusCode::NotAcceptable606,
+            code => StatusCode::Unregistered(code),
         }
     }
 
@@ -253,10 +664,22 @@ impl StatusCode {
             StatusCode::Decline => 603,
             StatusCode::DoesNotExistAnywhere => 604,
             StatusCode::NotAcceptable606 => 606,
+            StatusCode::Unregistered(code) => *code,
         }
     }
 
     pub fn to_reason_phrase(&self) -> &'static str {
+        if let StatusCode::Unregistered(_) = self {
+            return match self.class() {
+                StatusClass::Provisional => "Unknown Provisional Response",
+                StatusClass::Success => "Unknown Success Response",
+                StatusClass::Redirection => "Unknown Redirection",
+                StatusClass::ClientError => "Unknown Client Error",
+                StatusClass::ServerError => "Unknown Server Error",
+                StatusClass::GlobalFailure => "Unknown Global Failure",
+            };
+        }
+
         match self {
             StatusCode::Trying => "Trying",
             StatusCode::Ringing => "Ringing",
@@ -308,6 +731,7 @@ impl StatusCode {
             StatusCode::Decline => "Decline",
             StatusCode::DoesNotExistAnywhere => "Does Not Exist Anywhere",
             StatusCode::NotAcceptable606 => "Not Acceptable",
+            StatusCode::Unregistered(_) => unreachable!("handled by the early return above"),
         }
     }
 }
@@ -318,18 +742,148 @@ pub enum Version {
     Other(String),
 }
 
+impl Version {
+    pub fn from_str(s: &str) -> Self {
+        match s {
+            "SIP/1.0" => Version::V1,
+            "SIP/2.0" => Version::V2,
+            _ => Version::Other(s.to_string()),
+        }
+    }
+
+    pub fn to_str(&self) -> &str {
+        match self {
+            Version::V1 => "SIP/1.0",
+            Version::V2 => "SIP/2.0",
+            Version::Other(s) => s.as_str(),
+        }
+    }
+}
+
 pub struct Request {
     pub method: Method,
     pub uri: Uri,
     pub version: Version,
 }
 
+impl Request {
+    fn parse(line: &str, offset: usize) -> Result<Request, SipParseError> {
+        let mut parts = line.splitn(3, ' ');
+        let method_token = parts
+            .next()
+            .ok_or(SipParseError::MalformedStartLine { offset })?;
+        if !is_valid_token(method_token) {
+            return Err(SipParseError::UnknownMethod {
+                offset,
+                token: method_token.to_string(),
+            });
+        }
+        let method = Method::from_str(method_token);
+
+        let uri_text = parts
+            .next()
+            .ok_or(SipParseError::MalformedStartLine { offset })?;
+        let uri = Uri::parse(uri_text)?;
+
+        let version_text = parts
+            .next()
+            .ok_or(SipParseError::MalformedStartLine { offset })?;
+        let version = Version::from_str(version_text);
+
+        Ok(Request {
+            method,
+            uri,
+            version,
+        })
+    }
+}
+
+/// A SIP method token (RFC 3261 §25.1 `Method` / RFC 2616 `token`): one or
+/// more characters drawn from alphanumerics and a small set of symbols,
+/// never whitespace or control characters.
+fn is_valid_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-.!%*_+`'~".contains(c))
+}
+
+impl fmt::Display for Request {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.method.to_str(),
+            self.uri,
+            self.version.to_str()
+        )
+    }
+}
+
 pub struct Status {
     pub version: Version,
     pub status: StatusCode,
+    /// The reason phrase as the peer actually sent it (e.g. vendor text or
+    /// a localized string), so `Display` round-trips it byte-for-byte
+    /// instead of regenerating a generic one from `status`.
+    pub reason_phrase: String,
+}
+
+impl Status {
+    fn parse(line: &str, offset: usize) -> Result<Status, SipParseError> {
+        let mut parts = line.splitn(3, ' ');
+        let version_text = parts
+            .next()
+            .ok_or(SipParseError::MalformedStartLine { offset })?;
+        let version = Version::from_str(version_text);
+
+        let code_text = parts
+            .next()
+            .ok_or(SipParseError::MalformedStartLine { offset })?;
+        let code: u16 = code_text
+            .parse()
+            .map_err(|_| SipParseError::MalformedStartLine { offset })?;
+        if !(100..=699).contains(&code) {
+            return Err(SipParseError::InvalidStatusCode(code));
+        }
+        let status = StatusCode::from_code(code);
+        let reason_phrase = parts
+            .next()
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| status.to_reason_phrase().to_string());
+
+        Ok(Status {
+            version,
+            status,
+            reason_phrase,
+        })
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.version.to_str(),
+            self.status.to_code(),
+            self.reason_phrase
+        )
+    }
 }
 
 pub struct Header {
     pub name: String,
     pub value: String,
 }
+
+impl Header {
+    fn parse(line: &str, offset: usize) -> Result<Header, SipParseError> {
+        let (name, value) = line
+            .split_once(':')
+            .ok_or(SipParseError::MalformedHeader { offset })?;
+        Ok(Header {
+            name: name.trim().to_string(),
+            value: value.trim().to_string(),
+        })
+    }
+}